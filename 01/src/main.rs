@@ -1,133 +1,1042 @@
+use std::env;
+use std::fmt;
 use std::fs;
+use std::mem::replace;
 
 const INPUT_FILENAME: &str = "input.txt";
-const CODE_START: i32 = 50;
+const CODE_START: i64 = 50;
+const DIAL_SIZE: i64 = 100;
 
 #[derive(PartialEq)]
 enum CodeReaderState {
     SearchingDirection,
-    SearchingCount { positive: bool, buffer: String },
+    ReadingDial {
+        buffer: String,
+    },
+    SearchingAddressedDirection {
+        dial: usize,
+    },
+    SearchingCount {
+        positive: bool,
+        dial: usize,
+        buffer: String,
+    },
 }
 
+#[derive(Clone, Copy)]
 struct Move {
     moving_positive: bool,
-    length: i32,
+    length: i64,
+    /// addressed dial (1-based), instructions without `N:` prefix address the first one
+    dial: usize,
+    /// line of the instruction in the input (1-based)
+    line: usize,
 }
 struct CodeReader {
     state: CodeReaderState,
     moves: Vec<Move>,
+    /// number of dials instructions may address
+    dials: usize,
+    line: usize,
+    column: usize,
 }
 
-fn process_direction(input: char) -> CodeReaderState {
+#[derive(Debug, PartialEq)]
+enum ReadErrorKind {
+    /// a character that is not valid at this point of an instruction
+    UnexpectedCharacter(char),
+    /// a direction without any digits following it
+    MissingCount,
+    /// digits that do not fit into a move length
+    CountOutOfRange,
+    /// a dial prefix `N:` with `N` being zero or too large
+    InvalidDial,
+    /// a dial prefix `N:` addressing a dial beyond the configured ones
+    UnknownDial { dial: usize, dials: usize },
+    /// a dial prefix `N:` without any direction following it
+    MissingDirection,
+}
+
+/// error of the `CodeReader`, pointing at the offending location (1-based)
+#[derive(Debug, PartialEq)]
+struct ReadError {
+    line: usize,
+    column: usize,
+    kind: ReadErrorKind,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ReadErrorKind::UnexpectedCharacter(symbol) => write!(
+                f,
+                "unexpected character {:?} at line {}, column {}",
+                symbol, self.line, self.column
+            ),
+            ReadErrorKind::MissingCount => write!(
+                f,
+                "missing count at line {}, column {}",
+                self.line, self.column
+            ),
+            ReadErrorKind::CountOutOfRange => write!(
+                f,
+                "count out of range at line {}, column {}",
+                self.line, self.column
+            ),
+            ReadErrorKind::InvalidDial => write!(
+                f,
+                "invalid dial at line {}, column {}",
+                self.line, self.column
+            ),
+            ReadErrorKind::UnknownDial { dial, dials } => write!(
+                f,
+                "dial {} of {} dials at line {}, column {}",
+                dial, dials, self.line, self.column
+            ),
+            ReadErrorKind::MissingDirection => write!(
+                f,
+                "missing direction at line {}, column {}",
+                self.line, self.column
+            ),
+        }
+    }
+}
+
+fn start_count(input: char, dial: usize) -> Option<CodeReaderState> {
+    let positive = match input {
+        'L' => false,
+        'R' => true,
+        _ => return None,
+    };
+    Some(CodeReaderState::SearchingCount {
+        positive,
+        dial,
+        buffer: String::new(),
+    })
+}
+
+fn process_direction(input: char) -> Result<CodeReaderState, ReadErrorKind> {
+    if let Some(state) = start_count(input, 1) {
+        return Ok(state);
+    }
     match input {
-        'L' => CodeReaderState::SearchingCount {
-            positive: false,
-            buffer: String::new(),
-        },
-        'R' => CodeReaderState::SearchingCount {
-            positive: true,
-            buffer: String::new(),
-        },
-        _ => {
-            panic!("unexpected character in process_direction")
+        '0'..='9' => Ok(CodeReaderState::ReadingDial {
+            buffer: input.to_string(),
+        }),
+        '\r' | '\n' => Ok(CodeReaderState::SearchingDirection),
+        _ => Err(ReadErrorKind::UnexpectedCharacter(input)),
+    }
+}
+
+fn process_dial(
+    input: char,
+    mut buffer: String,
+    dials: usize,
+) -> Result<CodeReaderState, ReadErrorKind> {
+    match input {
+        '0'..='9' => {
+            buffer.push(input);
+            Ok(CodeReaderState::ReadingDial { buffer })
         }
+        ':' => match buffer.parse::<usize>() {
+            Ok(dial) if dial > dials => Err(ReadErrorKind::UnknownDial { dial, dials }),
+            Ok(dial) if dial > 0 => Ok(CodeReaderState::SearchingAddressedDirection { dial }),
+            _ => Err(ReadErrorKind::InvalidDial),
+        },
+        _ => Err(ReadErrorKind::UnexpectedCharacter(input)),
+    }
+}
+
+fn process_addressed_direction(input: char, dial: usize) -> Result<CodeReaderState, ReadErrorKind> {
+    start_count(input, dial).ok_or(ReadErrorKind::UnexpectedCharacter(input))
+}
+
+fn complete_move(
+    positive: bool,
+    dial: usize,
+    buffer: &str,
+    line: usize,
+) -> Result<Move, ReadErrorKind> {
+    if buffer.is_empty() {
+        return Err(ReadErrorKind::MissingCount);
+    }
+    match buffer.parse::<i64>() {
+        Ok(length) => Ok(Move {
+            moving_positive: positive,
+            length,
+            dial,
+            line,
+        }),
+        Err(_) => Err(ReadErrorKind::CountOutOfRange),
     }
 }
 
 fn process_count(
     input: char,
-    positive: &bool,
-    buffer: &String,
+    positive: bool,
+    dial: usize,
+    mut buffer: String,
+    line: usize,
     moves: &mut Vec<Move>,
-) -> CodeReaderState {
+) -> Result<CodeReaderState, ReadErrorKind> {
     match input {
         '0'..='9' => {
-            let mut new_buffer = buffer.clone();
-            new_buffer.push(input);
-            CodeReaderState::SearchingCount {
-                positive: *positive,
-                buffer: new_buffer,
-            }
+            buffer.push(input);
+            Ok(CodeReaderState::SearchingCount {
+                positive,
+                dial,
+                buffer,
+            })
         }
-        '\n' => {
-            let length = buffer.parse::<i32>();
-            if length.is_err() {
-                panic!("could not parse int {} error {:?}", buffer, length.err())
-            }
-            moves.push(Move {
-                moving_positive: *positive,
-                length: length.unwrap(),
-            });
-            CodeReaderState::SearchingDirection
-        }
-        _ => {
-            panic!("unexpected character in process_count")
+        '\r' | '\n' => {
+            moves.push(complete_move(positive, dial, &buffer, line)?);
+            Ok(CodeReaderState::SearchingDirection)
         }
+        _ => Err(ReadErrorKind::UnexpectedCharacter(input)),
     }
 }
 
 impl CodeReader {
-    pub fn new() -> Self {
+    pub fn new(dials: usize) -> Self {
         CodeReader {
             state: CodeReaderState::SearchingDirection,
             moves: vec![],
+            dials,
+            line: 1,
+            column: 0,
+        }
+    }
+
+    fn error(&self, kind: ReadErrorKind) -> ReadError {
+        ReadError {
+            line: self.line,
+            column: self.column,
+            kind,
         }
     }
 
-    pub fn read(&mut self, input: char) {
-        self.state = match &mut self.state {
+    pub fn read(&mut self, input: char) -> Result<(), ReadError> {
+        self.column += 1;
+        let state = replace(&mut self.state, CodeReaderState::SearchingDirection);
+        self.state = match state {
             CodeReaderState::SearchingDirection => process_direction(input),
-            CodeReaderState::SearchingCount { positive, buffer } => {
-                process_count(input, positive, buffer, &mut self.moves)
+            CodeReaderState::ReadingDial { buffer } => process_dial(input, buffer, self.dials),
+            CodeReaderState::SearchingAddressedDirection { dial } => {
+                process_addressed_direction(input, dial)
+            }
+            CodeReaderState::SearchingCount {
+                positive,
+                dial,
+                buffer,
+            } => process_count(input, positive, dial, buffer, self.line, &mut self.moves),
+        }
+        .map_err(|kind| self.error(kind))?;
+
+        if input == '\n' {
+            self.line += 1;
+            self.column = 0;
+        }
+        Ok(())
+    }
+
+    /// completes a pending move, in case the input does not end with a linebreak
+    pub fn finish(&mut self) -> Result<(), ReadError> {
+        let state = replace(&mut self.state, CodeReaderState::SearchingDirection);
+        self.column += 1;
+        match state {
+            CodeReaderState::SearchingDirection => {}
+            CodeReaderState::ReadingDial { .. }
+            | CodeReaderState::SearchingAddressedDirection { .. } => {
+                return Err(self.error(ReadErrorKind::MissingDirection));
+            }
+            CodeReaderState::SearchingCount {
+                positive,
+                dial,
+                buffer,
+            } => {
+                let current_move = complete_move(positive, dial, &buffer, self.line)
+                    .map_err(|kind| self.error(kind))?;
+                self.moves.push(current_move);
+            }
+        }
+        Ok(())
+    }
+
+    /// reads a whole instruction list for the given number of dials
+    pub fn read_all(input: &str, dials: usize) -> Result<Vec<Move>, ReadError> {
+        let mut code_reader = CodeReader::new(dials);
+        for char in input.chars() {
+            code_reader.read(char)?;
+        }
+        code_reader.finish()?;
+        Ok(code_reader.moves)
+    }
+}
+
+/// a lock dial with a configurable number of segments;
+/// positions always wrap into `0..segments` (Euclidean remainder)
+struct Dial {
+    segments: i64,
+    position: i64,
+}
+
+/// result of rotating a dial by one move
+#[derive(Debug, PartialEq)]
+struct RotationOutcome {
+    /// position of the dial after the move
+    position: i64,
+    /// how often the dial pointed at zero during the move (including the end)
+    zero_hits: i64,
+    /// full turns passed to the following dial of an odometer,
+    /// negative when turning left past zero towards `segments - 1`
+    carry: i64,
+}
+
+impl Dial {
+    /// panics if the dial has no segments
+    pub fn new(segments: i64, start: i64) -> Self {
+        if segments <= 0 {
+            panic!("a dial needs at least one segment, got {}", segments);
+        }
+        Dial {
+            segments,
+            position: start.rem_euclid(segments),
+        }
+    }
+
+    /// counts how often the dial points at zero while performing a move,
+    /// in constant time regardless of the move length
+    fn count_zero_hits(&self, current_move: &Move) -> i64 {
+        let mut distance_to_zero = if current_move.moving_positive {
+            self.segments - self.position
+        } else {
+            self.position
+        };
+        if distance_to_zero == 0 {
+            distance_to_zero = self.segments;
+        }
+
+        if current_move.length < distance_to_zero {
+            return 0;
+        }
+        1 + (current_move.length - distance_to_zero) / self.segments
+    }
+
+    pub fn rotate(&mut self, current_move: &Move) -> RotationOutcome {
+        let zero_hits = self.count_zero_hits(current_move);
+        let offset = if current_move.moving_positive {
+            current_move.length
+        } else {
+            -current_move.length
+        };
+        let unwrapped = self.position + offset.rem_euclid(self.segments);
+        let carry = offset.div_euclid(self.segments) + unwrapped.div_euclid(self.segments);
+        self.position = unwrapped.rem_euclid(self.segments);
+
+        RotationOutcome {
+            position: self.position,
+            zero_hits,
+            carry,
+        }
+    }
+}
+
+/// chained dials, where every full turn of a dial (passing between
+/// `segments - 1` and zero) turns the following dial by one segment;
+/// turns of the last dial are lost
+struct Odometer {
+    dials: Vec<Dial>,
+}
+
+impl Odometer {
+    pub fn new(dials: Vec<Dial>) -> Self {
+        Odometer { dials }
+    }
+
+    /// rotates the addressed dial and carries into the following ones,
+    /// returns the move and outcome of every dial that rotated
+    ///
+    /// panics if the move addresses a dial that does not exist
+    pub fn rotate(&mut self, current_move: &Move) -> Vec<(Move, RotationOutcome)> {
+        if current_move.dial == 0 || current_move.dial > self.dials.len() {
+            panic!(
+                "line {} addresses dial {} of {}",
+                current_move.line,
+                current_move.dial,
+                self.dials.len()
+            );
+        }
+        let mut rotations = vec![];
+        let mut dial_move = *current_move;
+
+        loop {
+            let outcome = self.dials[dial_move.dial - 1].rotate(&dial_move);
+            let carry = outcome.carry;
+            rotations.push((dial_move, outcome));
+
+            if carry == 0 || dial_move.dial == self.dials.len() {
+                return rotations;
+            }
+            dial_move = Move {
+                moving_positive: carry > 0,
+                length: carry.abs(),
+                dial: dial_move.dial + 1,
+                line: current_move.line,
+            };
+        }
+    }
+}
+
+enum TraceFormat {
+    Csv,
+    JsonLines,
+}
+
+/// everything that happened to a dial during a single move
+struct TraceRecord {
+    line: usize,
+    dial: usize,
+    moving_positive: bool,
+    length: i64,
+    position_before: i64,
+    position_after: i64,
+    zero_hits_part1: i64,
+    zero_hits_part2: i64,
+}
+
+const TRACE_CSV_HEADER: &str =
+    "line,dial,direction,length,position_before,position_after,zero_hits_part1,zero_hits_part2";
+
+impl TraceRecord {
+    pub fn new(current_move: &Move, position_before: i64, outcome: &RotationOutcome) -> Self {
+        TraceRecord {
+            line: current_move.line,
+            dial: current_move.dial,
+            moving_positive: current_move.moving_positive,
+            length: current_move.length,
+            position_before,
+            position_after: outcome.position,
+            zero_hits_part1: if outcome.position == 0 { 1 } else { 0 },
+            zero_hits_part2: outcome.zero_hits,
+        }
+    }
+
+    fn direction(&self) -> char {
+        if self.moving_positive { 'R' } else { 'L' }
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.line,
+            self.dial,
+            self.direction(),
+            self.length,
+            self.position_before,
+            self.position_after,
+            self.zero_hits_part1,
+            self.zero_hits_part2
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"line\":{},\"dial\":{},\"direction\":\"{}\",\"length\":{},\"position_before\":{},\"position_after\":{},\"zero_hits_part1\":{},\"zero_hits_part2\":{}}}",
+            self.line,
+            self.dial,
+            self.direction(),
+            self.length,
+            self.position_before,
+            self.position_after,
+            self.zero_hits_part1,
+            self.zero_hits_part2
+        )
+    }
+}
+
+enum Mode {
+    /// print the codes of both parts
+    Codes,
+    /// print one record per move instead of the codes
+    Trace(TraceFormat),
+    /// print the shortest instructions reaching the targets
+    Solve {
+        segments: i64,
+        start: i64,
+        targets: Vec<i64>,
+    },
+}
+
+fn parse_number(arg: &str) -> i64 {
+    match arg.parse::<i64>() {
+        Ok(number) => number,
+        Err(err) => panic!("could not parse number >{}<: {}", arg, err),
+    }
+}
+
+/// removes `--dials=<segments>,<segments>,...` from the arguments and returns
+/// the segment count of every dial, None if not given
+fn parse_dial_sizes(args: &mut Vec<String>) -> Option<Vec<i64>> {
+    let mut dial_sizes = None;
+    args.retain(|arg| match arg.strip_prefix("--dials=") {
+        Some(sizes) => {
+            dial_sizes = Some(sizes.split(',').map(parse_number).collect());
+            false
+        }
+        None => true,
+    });
+    dial_sizes
+}
+
+/// no arguments, `--trace=csv`, `--trace=jsonl` or `--solve <segments> <start> <target>...`
+fn parse_mode(args: &[String]) -> Mode {
+    match args.first().map(String::as_str) {
+        None => Mode::Codes,
+        Some("--trace=csv") => Mode::Trace(TraceFormat::Csv),
+        Some("--trace=jsonl") => Mode::Trace(TraceFormat::JsonLines),
+        Some("--solve") => {
+            if args.len() < 3 {
+                panic!("usage: --solve <segments> <start> <target>...");
             }
+            Mode::Solve {
+                segments: parse_number(&args[1]),
+                start: parse_number(&args[2]),
+                targets: args[3..].iter().map(|arg| parse_number(arg)).collect(),
+            }
+        }
+        Some(arg) => panic!(
+            "unknown argument >{}<, expected --trace=csv, --trace=jsonl or --solve",
+            arg
+        ),
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = if self.moving_positive { 'R' } else { 'L' };
+        if self.dial != 1 {
+            write!(f, "{}:", self.dial)?;
+        }
+        write!(f, "{}{}", direction, self.length)
+    }
+}
+
+/// finds the moves with the least total rotation that point the dial at
+/// every target in the given order. As the dial rests on each target,
+/// every leg is independent and simply takes the shorter way round;
+/// targets the dial already points at need no move.
+///
+/// panics if segments <= 0, like `Dial::new`
+fn find_shortest_moves(segments: i64, start: i64, targets: &[i64]) -> Vec<Move> {
+    let mut moves = vec![];
+    let mut position = Dial::new(segments, start).position;
+
+    for target in targets {
+        let target = target.rem_euclid(segments);
+        let distance_right = (target - position).rem_euclid(segments);
+        let distance_left = (position - target).rem_euclid(segments);
+        if distance_right != 0 {
+            moves.push(Move {
+                moving_positive: distance_right <= distance_left,
+                length: distance_right.min(distance_left),
+                dial: 1,
+                line: moves.len() + 1,
+            });
         }
+        position = target;
     }
+    moves
 }
 
 fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let dial_sizes = parse_dial_sizes(&mut args);
+    let trace_format = match parse_mode(&args) {
+        Mode::Codes => None,
+        Mode::Trace(format) => Some(format),
+        Mode::Solve {
+            segments,
+            start,
+            targets,
+        } => {
+            if dial_sizes.is_some() {
+                panic!(
+                    "--solve takes the segments as argument and can not be combined with --dials"
+                );
+            }
+            for current_move in find_shortest_moves(segments, start, &targets) {
+                println!("{}", current_move);
+            }
+            return;
+        }
+    };
+    /* by default a single dial with `DIAL_SIZE` segments */
+    let dial_sizes = dial_sizes.unwrap_or(vec![DIAL_SIZE]);
+
     let code = match fs::read_to_string(INPUT_FILENAME) {
         Ok(file) => file,
         Err(err) => panic!("Could not read file: {}", err),
     };
-    let mut aoc0101_position = CODE_START;
-    let mut aoc0101_count = 0;
+    /* the first dial starts at the code start, any further dial at zero */
+    let mut odometer = Odometer::new(
+        dial_sizes
+            .iter()
+            .enumerate()
+            .map(|(index, &segments)| Dial::new(segments, if index == 0 { CODE_START } else { 0 }))
+            .collect(),
+    );
+    let mut aoc0101_counts = vec![0; dial_sizes.len()];
+    let mut aoc0102_counts = vec![0; dial_sizes.len()];
 
-    let mut aoc0102_position = CODE_START;
-    let mut aoc0102_count = 0;
+    let moves = match CodeReader::read_all(&code, dial_sizes.len()) {
+        Ok(moves) => moves,
+        Err(err) => panic!("Could not read instructions: {}", err),
+    };
 
-    let mut code_reader = CodeReader::new();
-    for char in code.chars() {
-        code_reader.read(char);
+    if let Some(TraceFormat::Csv) = trace_format {
+        println!("{}", TRACE_CSV_HEADER);
     }
 
-    for current_move in &code_reader.moves {
-        /* AoC 2025 - Day 1 - Part 1 */
-        /* Count when zero after rotation */
-        aoc0101_position += if current_move.moving_positive {
-            current_move.length
-        } else {
-            -current_move.length
+    for current_move in &moves {
+        let positions_before: Vec<i64> = odometer.dials.iter().map(|dial| dial.position).collect();
+
+        for (dial_move, outcome) in odometer.rotate(current_move) {
+            let index = dial_move.dial - 1;
+
+            if let Some(format) = &trace_format {
+                let record = TraceRecord::new(&dial_move, positions_before[index], &outcome);
+                match format {
+                    TraceFormat::Csv => println!("{}", record.to_csv()),
+                    TraceFormat::JsonLines => println!("{}", record.to_json()),
+                }
+            }
+
+            /* AoC 2025 - Day 1 - Part 1 */
+            /* Count when zero after rotation */
+            if outcome.position == 0 {
+                aoc0101_counts[index] += 1;
+            }
+
+            /* AoC 2025 - Day 1 - Part 2 */
+            /* Count every zero hit */
+            aoc0102_counts[index] += outcome.zero_hits;
+        }
+    }
+
+    if trace_format.is_some() {
+        return;
+    }
+    println!("The AoC 01-01 code is {}", aoc0101_counts[0]);
+    println!("The AoC 01-02 code is {}", aoc0102_counts[0]);
+    for index in 1..dial_sizes.len() {
+        println!(
+            "Dial {} ended at zero {} times and hit zero {} times",
+            index + 1,
+            aoc0101_counts[index],
+            aoc0102_counts[index]
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use crate::{
+        CodeReader, DIAL_SIZE, Dial, Move, Odometer, ReadError, ReadErrorKind, RotationOutcome,
+        TraceRecord, find_shortest_moves,
+    };
+
+    /// rotates click by click, counting every zero hit (the original part 2 approach)
+    fn count_zero_hits_stepwise(mut position: i64, current_move: &Move) -> i64 {
+        let mut count = 0;
+        for _ in 0..current_move.length {
+            position += if current_move.moving_positive { 1 } else { -1 };
+            position = position.rem_euclid(DIAL_SIZE);
+            if position == 0 {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// small xorshift generator, good enough to produce test moves
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn Dial_rotate__starting_at_zero__does_not_count_start() {
+        let left = Move {
+            moving_positive: false,
+            length: 99,
+            dial: 1,
+            line: 1,
         };
-        aoc0101_position %= 100;
+        let right = Move {
+            moving_positive: true,
+            length: 99,
+            dial: 1,
+            line: 1,
+        };
+        assert_eq!(0, Dial::new(DIAL_SIZE, 0).rotate(&left).zero_hits);
+        assert_eq!(0, Dial::new(DIAL_SIZE, 0).rotate(&right).zero_hits);
+    }
 
-        if aoc0101_position == 0 {
-            aoc0101_count += 1;
+    #[test]
+    fn Dial_rotate__turning_left_onto_zero__counts_hit() {
+        let current_move = Move {
+            moving_positive: false,
+            length: 50,
+            dial: 1,
+            line: 1,
+        };
+        assert_eq!(1, Dial::new(DIAL_SIZE, 50).rotate(&current_move).zero_hits);
+        assert_eq!(0, Dial::new(DIAL_SIZE, 51).rotate(&current_move).zero_hits);
+    }
+
+    #[test]
+    fn Dial_rotate__for_huge_moves__returns_full_turns() {
+        let current_move = Move {
+            moving_positive: true,
+            length: 9999999999,
+            dial: 1,
+            line: 1,
+        };
+        assert_eq!(
+            100000000,
+            Dial::new(DIAL_SIZE, 1).rotate(&current_move).zero_hits
+        );
+        assert_eq!(
+            99999999,
+            Dial::new(DIAL_SIZE, 0).rotate(&current_move).zero_hits
+        );
+    }
+
+    #[test]
+    fn Dial_rotate__for_random_moves__matches_stepwise_rotation() {
+        let mut state = 0x2025_0101;
+        for _ in 0..10000 {
+            let position = (next_random(&mut state) % DIAL_SIZE as u64) as i64;
+            let current_move = Move {
+                moving_positive: next_random(&mut state).is_multiple_of(2),
+                length: (next_random(&mut state) % 1000) as i64,
+                dial: 1,
+                line: 1,
+            };
+            assert_eq!(
+                count_zero_hits_stepwise(position, &current_move),
+                Dial::new(DIAL_SIZE, position)
+                    .rotate(&current_move)
+                    .zero_hits
+            );
         }
+    }
 
-        /* AoC 2025 - Day 1 - Part 2 */
-        /* Count every zero hit */
-        let mut aoc0102_rotation = current_move.length;
-        while aoc0102_rotation > 0 {
-            aoc0102_position += if current_move.moving_positive { 1 } else { -1 };
-            aoc0102_position %= 100;
+    #[test]
+    fn Dial_new__given_negative_start__wraps_into_range() {
+        assert_eq!(90, Dial::new(DIAL_SIZE, -10).position);
+        assert_eq!(3, Dial::new(7, 17).position);
+    }
+
+    #[test]
+    #[should_panic]
+    fn Dial_new__without_segments__panics() {
+        Dial::new(0, 0);
+    }
+
+    #[test]
+    fn Dial_rotate__turning_left_past_zero__stays_non_negative() {
+        let mut dial = Dial::new(DIAL_SIZE, 5);
+        let current_move = Move {
+            moving_positive: false,
+            length: 10,
+            dial: 1,
+            line: 1,
+        };
+        assert_eq!(
+            RotationOutcome {
+                position: 95,
+                zero_hits: 1,
+                carry: -1
+            },
+            dial.rotate(&current_move)
+        );
+    }
+
+    #[test]
+    fn Dial_rotate__with_other_segment_count__wraps_at_segment_count() {
+        let mut dial = Dial::new(12, 10);
+        let current_move = Move {
+            moving_positive: true,
+            length: 27,
+            dial: 1,
+            line: 1,
+        };
+        assert_eq!(
+            RotationOutcome {
+                position: 1,
+                zero_hits: 3,
+                carry: 3
+            },
+            dial.rotate(&current_move)
+        );
+    }
+
+    #[test]
+    fn CodeReader_read_all__without_final_linebreak__keeps_last_move() {
+        let moves = CodeReader::read_all("L25\nR10", 1).unwrap();
+        assert_eq!(2, moves.len());
+        assert!(moves[1].moving_positive);
+        assert_eq!(10, moves[1].length);
+    }
+
+    #[test]
+    fn CodeReader_read_all__with_crlf__reads_moves() {
+        let moves = CodeReader::read_all("L25\r\nR10\r\n", 1).unwrap();
+        assert_eq!(2, moves.len());
+        assert_eq!(25, moves[0].length);
+        assert_eq!(10, moves[1].length);
+    }
+
+    #[test]
+    fn CodeReader_read_all__with_blank_lines__skips_them() {
+        let moves = CodeReader::read_all("\nL25\n\n\r\nR10\n\n", 1).unwrap();
+        assert_eq!(2, moves.len());
+    }
+
+    #[test]
+    fn CodeReader_read_all__given_unexpected_character__reports_location() {
+        assert_eq!(
+            Err(ReadError {
+                line: 2,
+                column: 3,
+                kind: ReadErrorKind::UnexpectedCharacter('x')
+            }),
+            CodeReader::read_all("L25\nR1x0\n", 1).map(|moves| moves.len())
+        );
+        assert_eq!(
+            Err(ReadError {
+                line: 3,
+                column: 1,
+                kind: ReadErrorKind::UnexpectedCharacter(' ')
+            }),
+            CodeReader::read_all("L25\r\nR10\r\n R1", 1).map(|moves| moves.len())
+        );
+    }
+
+    #[test]
+    fn CodeReader_read_all__given_direction_without_count__reports_missing_count() {
+        assert_eq!(
+            Err(ReadError {
+                line: 1,
+                column: 2,
+                kind: ReadErrorKind::MissingCount
+            }),
+            CodeReader::read_all("L\nR10\n", 1).map(|moves| moves.len())
+        );
+        assert_eq!(
+            Err(ReadError {
+                line: 2,
+                column: 2,
+                kind: ReadErrorKind::MissingCount
+            }),
+            CodeReader::read_all("L10\nR", 1).map(|moves| moves.len())
+        );
+    }
+
+    #[test]
+    fn CodeReader_read_all__given_huge_count__reports_out_of_range() {
+        assert_eq!(
+            Err(ReadError {
+                line: 1,
+                column: 22,
+                kind: ReadErrorKind::CountOutOfRange
+            }),
+            CodeReader::read_all("R99999999999999999999\n", 1).map(|moves| moves.len())
+        );
+    }
+
+    #[test]
+    fn CodeReader_read_all__for_each_move__records_input_line() {
+        let moves = CodeReader::read_all("L25\n\nR10\r\nL3", 1).unwrap();
+        let lines: Vec<usize> = moves.iter().map(|current_move| current_move.line).collect();
+        assert_eq!(vec![1, 3, 4], lines);
+    }
+
+    #[test]
+    fn TraceRecord__for_move_onto_zero__formats_csv_and_json() {
+        let mut dial = Dial::new(DIAL_SIZE, 50);
+        let current_move = Move {
+            moving_positive: false,
+            length: 150,
+            dial: 1,
+            line: 7,
+        };
+        let outcome = dial.rotate(&current_move);
+        let record = TraceRecord::new(&current_move, 50, &outcome);
+
+        assert_eq!("7,1,L,150,50,0,1,2", record.to_csv());
+        assert_eq!(
+            "{\"line\":7,\"dial\":1,\"direction\":\"L\",\"length\":150,\"position_before\":50,\"position_after\":0,\"zero_hits_part1\":1,\"zero_hits_part2\":2}",
+            record.to_json()
+        );
+    }
 
-            if aoc0102_position == 0 {
-                aoc0102_count += 1;
+    #[test]
+    fn find_shortest_moves__for_each_target__takes_shorter_direction() {
+        let moves = find_shortest_moves(DIAL_SIZE, 50, &[25, 85, 5, 5, 50]);
+        let instructions: Vec<String> = moves.iter().map(|m| m.to_string()).collect();
+        assert_eq!(vec!["L25", "L40", "R20", "R45"], instructions);
+    }
+
+    #[test]
+    fn find_shortest_moves__for_opposite_target__picks_either_half_turn() {
+        let moves = find_shortest_moves(10, 0, &[5]);
+        assert_eq!(1, moves.len());
+        assert_eq!(5, moves[0].length);
+    }
+
+    #[test]
+    #[should_panic(expected = "a dial needs at least one segment")]
+    fn find_shortest_moves__given_no_segments__panics() {
+        find_shortest_moves(0, 0, &[5]);
+    }
+
+    #[test]
+    fn find_shortest_moves__written_as_instructions__round_trip_through_simulator() {
+        let mut state = 0x2025_0105;
+        for _ in 0..100 {
+            let segments = 1 + (next_random(&mut state) % 200) as i64;
+            let start = (next_random(&mut state) % segments as u64) as i64;
+            let targets: Vec<i64> = (0..20)
+                .map(|_| (next_random(&mut state) % segments as u64) as i64)
+                .collect();
+
+            let moves = find_shortest_moves(segments, start, &targets);
+            let instructions: String = moves.iter().map(|m| format!("{}\n", m)).collect();
+            let read_moves = CodeReader::read_all(&instructions, 1).unwrap();
+
+            let mut dial = Dial::new(segments, start);
+            let mut visited = vec![];
+            for current_move in &read_moves {
+                assert!(2 * current_move.length <= segments);
+                visited.push(dial.rotate(current_move).position);
             }
-            aoc0102_rotation -= 1;
+            let mut expected = targets.clone();
+            expected.insert(0, start);
+            expected.dedup();
+            visited.insert(0, start);
+            assert_eq!(expected, visited);
         }
     }
 
-    println!("The AoC 01-01 code is {}", aoc0101_count);
-    println!("The AoC 01-02 code is {}", aoc0102_count);
+    #[test]
+    fn CodeReader_read_all__with_dial_prefix__addresses_dial() {
+        let moves = CodeReader::read_all("L25\n2:R10\n12:L3", 12).unwrap();
+        let dials: Vec<usize> = moves.iter().map(|current_move| current_move.dial).collect();
+        assert_eq!(vec![1, 2, 12], dials);
+        assert_eq!("2:R10", moves[1].to_string());
+        assert_eq!("L25", moves[0].to_string());
+    }
+
+    #[test]
+    fn CodeReader_read_all__given_invalid_dial_prefix__reports_error() {
+        assert_eq!(
+            Err(ReadError {
+                line: 1,
+                column: 2,
+                kind: ReadErrorKind::InvalidDial
+            }),
+            CodeReader::read_all("0:L5\n", 1).map(|moves| moves.len())
+        );
+        assert_eq!(
+            Err(ReadError {
+                line: 1,
+                column: 3,
+                kind: ReadErrorKind::UnexpectedCharacter('\n')
+            }),
+            CodeReader::read_all("2:\nL5", 2).map(|moves| moves.len())
+        );
+        assert_eq!(
+            Err(ReadError {
+                line: 1,
+                column: 2,
+                kind: ReadErrorKind::MissingDirection
+            }),
+            CodeReader::read_all("2", 2).map(|moves| moves.len())
+        );
+    }
+
+    #[test]
+    fn CodeReader_read_all__addressing_dial_beyond_dials__reports_error() {
+        let result = CodeReader::read_all("L5\n2:R3\n3:L1\n", 2).map(|moves| moves.len());
+        assert_eq!(
+            Err(ReadError {
+                line: 3,
+                column: 2,
+                kind: ReadErrorKind::UnknownDial { dial: 3, dials: 2 }
+            }),
+            result
+        );
+        assert_eq!(
+            "dial 3 of 2 dials at line 3, column 2",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn Odometer_rotate__passing_zero__carries_into_following_dials() {
+        let mut odometer = Odometer::new(vec![Dial::new(10, 5), Dial::new(10, 9), Dial::new(4, 0)]);
+        let moves = CodeReader::read_all("R25", 3).unwrap();
+
+        let rotations = odometer.rotate(&moves[0]);
+
+        let positions: Vec<i64> = odometer.dials.iter().map(|dial| dial.position).collect();
+        assert_eq!(vec![0, 2, 1], positions);
+        let zero_hits: Vec<(usize, i64)> = rotations
+            .iter()
+            .map(|(dial_move, outcome)| (dial_move.dial, outcome.zero_hits))
+            .collect();
+        assert_eq!(vec![(1, 3), (2, 1), (3, 0)], zero_hits);
+    }
+
+    #[test]
+    fn Odometer_rotate__turning_left_past_zero__borrows_from_following_dial() {
+        let mut odometer = Odometer::new(vec![Dial::new(10, 0), Dial::new(10, 0)]);
+        let moves = CodeReader::read_all("L1\n2:R3\nR1", 2).unwrap();
+
+        odometer.rotate(&moves[0]);
+        let positions: Vec<i64> = odometer.dials.iter().map(|dial| dial.position).collect();
+        assert_eq!(vec![9, 9], positions);
+
+        assert_eq!(1, odometer.rotate(&moves[1]).len());
+        assert_eq!(2, odometer.rotate(&moves[2]).len());
+        let positions: Vec<i64> = odometer.dials.iter().map(|dial| dial.position).collect();
+        assert_eq!(vec![0, 3], positions);
+    }
+
+    #[test]
+    fn Odometer_rotate__with_single_dial__matches_dial() {
+        let moves =
+            CodeReader::read_all("L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82", 1).unwrap();
+        let mut odometer = Odometer::new(vec![Dial::new(DIAL_SIZE, 50)]);
+        let mut dial = Dial::new(DIAL_SIZE, 50);
+
+        for current_move in &moves {
+            let rotations = odometer.rotate(current_move);
+            assert_eq!(1, rotations.len());
+            assert_eq!(dial.rotate(current_move), rotations[0].1);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn Odometer_rotate__addressing_missing_dial__panics() {
+        let mut odometer = Odometer::new(vec![Dial::new(DIAL_SIZE, 50)]);
+        let moves = CodeReader::read_all("2:L5", 2).unwrap();
+        odometer.rotate(&moves[0]);
+    }
 }
@@ -1,33 +1,179 @@
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::mem::replace;
+use std::ops::RangeInclusive;
+/// `complete` marks a number followed by whitespace, so no more digits may follow
 enum ReaderState {
-    ReadingStart { buffer: String },
-    ReadingEnd { start: String, buffer: String },
-    DoneReading,
+    ReadingStart {
+        buffer: String,
+        complete: bool,
+    },
+    ReadingEnd {
+        start: String,
+        buffer: String,
+        complete: bool,
+    },
+    /// after an error, which is returned again on any further input
+    Failed(ReadError),
     InvalidState,
 }
 
 struct Reader {
     state: ReaderState,
+    offset: usize,
+    base: u32,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+enum ReadErrorKind {
+    /// a character that is not valid at this point of a range
+    UnexpectedCharacter(char),
+    /// a range without start or end
+    MissingNumber,
+    /// digits that do not fit into an id
+    NumberOutOfRange,
+    /// a range ending before it starts
+    StartAfterEnd { start: u64, end: u64 },
+}
+
+/// error of the `Reader`, pointing at the byte offset (0-based) where it was detected
+#[derive(Debug, PartialEq, Clone)]
+struct ReadError {
+    offset: usize,
+    kind: ReadErrorKind,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ReadErrorKind::UnexpectedCharacter(symbol) => {
+                write!(f, "unexpected character {:?}", symbol)?
+            }
+            ReadErrorKind::MissingNumber => write!(f, "missing number")?,
+            ReadErrorKind::NumberOutOfRange => write!(f, "number out of range")?,
+            ReadErrorKind::StartAfterEnd { start, end } => {
+                write!(f, "range {}-{} starts after its end", start, end)?
+            }
+        }
+        write!(f, " at byte offset {}", self.offset)
+    }
+}
+
+#[derive(Debug, PartialEq)]
 struct ProductRange {
-    start: i64,
-    end: i64,
+    start: u64,
+    end: u64,
 }
 
-fn read_start(input: char, mut buffer: String) -> ReaderState {
+impl ProductRange {
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.start = min(self.start, other.start);
+        self.end = max(self.end, other.end);
+    }
+
+    /// lazily yields the invalid ids within the range in ascending order,
+    /// for ids written in decimal unless changed with `InvalidIds::in_base`
+    pub fn invalid_ids(&self, rule: RepetitionRule) -> InvalidIds {
+        InvalidIds {
+            rule,
+            base: DECIMAL,
+            products: ProductRange {
+                start: self.start,
+                end: self.end,
+            },
+            length: 1,
+            streams: vec![],
+        }
+    }
+}
+
+/// finds all pairs of ranges (as indices in input order) sharing at least one id
+fn find_overlaps(ranges: &[ProductRange]) -> Vec<(usize, usize)> {
+    let mut overlaps = vec![];
+    for (first, first_range) in ranges.iter().enumerate() {
+        for (second, second_range) in ranges.iter().enumerate().skip(first + 1) {
+            if first_range.intersects(second_range) {
+                overlaps.push((first, second));
+            }
+        }
+    }
+    overlaps
+}
+
+/// sorts the ranges and merges overlapping ones, so every id is covered once
+fn merge_ranges(mut ranges: Vec<ProductRange>) -> Vec<ProductRange> {
+    // after sorting by start, any later range may only intersect the last merged one
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged_ranges: Vec<ProductRange> = vec![];
+    for range in ranges {
+        if let Some(merged) = merged_ranges.last_mut()
+            && merged.intersects(&range)
+        {
+            merged.merge(&range);
+        } else {
+            merged_ranges.push(range);
+        }
+    }
+    merged_ranges
+}
+
+fn is_whitespace(input: char) -> bool {
+    matches!(input, ' ' | '\t' | '\r')
+}
+
+fn is_separator(input: char) -> bool {
+    matches!(input, ',' | '\n')
+}
+
+fn parse_id(buffer: &str, base: u32) -> Result<u64, ReadErrorKind> {
+    if buffer.is_empty() {
+        return Err(ReadErrorKind::MissingNumber);
+    }
+    u64::from_str_radix(buffer, base).map_err(|_| ReadErrorKind::NumberOutOfRange)
+}
+
+fn complete_range(start: &str, end: &str, base: u32) -> Result<ProductRange, ReadErrorKind> {
+    let start = parse_id(start, base)?;
+    let end = parse_id(end, base)?;
+    if start > end {
+        return Err(ReadErrorKind::StartAfterEnd { start, end });
+    }
+    Ok(ProductRange { start, end })
+}
+
+fn read_start(
+    input: char,
+    mut buffer: String,
+    complete: bool,
+    base: u32,
+) -> Result<ReaderState, ReadErrorKind> {
     match input {
-        '0'..='9' => {
+        _ if input.is_digit(base) && !complete => {
             buffer.push(input);
-            ReaderState::ReadingStart { buffer }
+            Ok(ReaderState::ReadingStart { buffer, complete })
         }
-        '-' => ReaderState::ReadingEnd {
-            start: buffer,
-            buffer: String::new(),
-        },
-        _ => panic!("unexpected character {} in read_start", input),
+        '-' => {
+            parse_id(&buffer, base)?;
+            Ok(ReaderState::ReadingEnd {
+                start: buffer,
+                buffer: String::new(),
+                complete: false,
+            })
+        }
+        _ if is_whitespace(input) => Ok(ReaderState::ReadingStart {
+            complete: !buffer.is_empty(),
+            buffer,
+        }),
+        _ if is_separator(input) && buffer.is_empty() => {
+            Ok(ReaderState::ReadingStart { buffer, complete })
+        }
+        _ => Err(ReadErrorKind::UnexpectedCharacter(input)),
     }
 }
 
@@ -35,35 +181,32 @@ fn read_end(
     input: char,
     start: String,
     mut buffer: String,
+    complete: bool,
+    base: u32,
     result: &mut Option<ProductRange>,
-) -> ReaderState {
+) -> Result<ReaderState, ReadErrorKind> {
     match input {
-        '0'..='9' => {
+        _ if input.is_digit(base) && !complete => {
             buffer.push(input);
-            ReaderState::ReadingEnd { start, buffer }
-        }
-        ',' => {
-            let num_start = start.parse::<i64>().unwrap();
-            let num_end = buffer.parse::<i64>().unwrap();
-            *result = Some(ProductRange {
-                start: num_start,
-                end: num_end,
-            });
-            ReaderState::ReadingStart {
-                buffer: String::new(),
-            }
+            Ok(ReaderState::ReadingEnd {
+                start,
+                buffer,
+                complete,
+            })
         }
-        '\r' => ReaderState::ReadingEnd { start, buffer },
-        '\n' => {
-            let num_start = start.parse::<i64>().unwrap();
-            let num_end = buffer.parse::<i64>().unwrap();
-            *result = Some(ProductRange {
-                start: num_start,
-                end: num_end,
-            });
-            ReaderState::DoneReading
+        _ if is_whitespace(input) => Ok(ReaderState::ReadingEnd {
+            start,
+            complete: !buffer.is_empty(),
+            buffer,
+        }),
+        _ if is_separator(input) => {
+            *result = Some(complete_range(&start, &buffer, base)?);
+            Ok(ReaderState::ReadingStart {
+                buffer: String::new(),
+                complete: false,
+            })
         }
-        _ => panic!("unexpected character {} in read_end", input),
+        _ => Err(ReadErrorKind::UnexpectedCharacter(input)),
     }
 }
 
@@ -72,63 +215,148 @@ impl Reader {
         Reader {
             state: ReaderState::ReadingStart {
                 buffer: String::new(),
+                complete: false,
             },
+            offset: 0,
+            base: DECIMAL,
         }
     }
 
-    pub fn read(&mut self, input: char) -> Option<ProductRange> {
+    /// reads ids written with digits of the given base (2 to 36)
+    pub fn with_base(mut self, base: u32) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn read(&mut self, input: char) -> Result<Option<ProductRange>, ReadError> {
         let state = replace(&mut self.state, ReaderState::InvalidState);
         let mut product_range = Option::<ProductRange>::None;
 
-        self.state = match state {
-            ReaderState::ReadingStart { buffer } => read_start(input, buffer),
-            ReaderState::ReadingEnd { start, buffer } => {
-                read_end(input, start, buffer, &mut product_range)
+        let next_state = match state {
+            ReaderState::ReadingStart { buffer, complete } => {
+                read_start(input, buffer, complete, self.base)
             }
-            ReaderState::DoneReading => panic!("received data after end"),
+            ReaderState::ReadingEnd {
+                start,
+                buffer,
+                complete,
+            } => read_end(
+                input,
+                start,
+                buffer,
+                complete,
+                self.base,
+                &mut product_range,
+            ),
+            ReaderState::Failed(err) => Err(err.kind),
             ReaderState::InvalidState => panic!("received data in temporary invalid state"),
         };
+        self.state = next_state.map_err(|kind| self.fail(kind))?;
+        self.offset += input.len_utf8();
+
+        Ok(product_range)
+    }
 
-        product_range
+    /// completes a pending range, in case the input does not end with a separator
+    pub fn finish(&mut self) -> Result<Option<ProductRange>, ReadError> {
+        let state = replace(&mut self.state, ReaderState::InvalidState);
+        let result = match state {
+            ReaderState::ReadingStart { buffer, .. } if buffer.is_empty() => Ok(None),
+            ReaderState::ReadingStart { .. } => Err(ReadErrorKind::MissingNumber),
+            ReaderState::ReadingEnd { start, buffer, .. } => {
+                complete_range(&start, &buffer, self.base).map(Some)
+            }
+            ReaderState::Failed(err) => Err(err.kind),
+            ReaderState::InvalidState => panic!("finished in temporary invalid state"),
+        };
+        self.state = ReaderState::ReadingStart {
+            buffer: String::new(),
+            complete: false,
+        };
+        result.map_err(|kind| self.fail(kind))
     }
+
+    /// keeps the error, so the reader stays failed instead of reading on
+    fn fail(&mut self, kind: ReadErrorKind) -> ReadError {
+        let err = match &self.state {
+            ReaderState::Failed(err) => err.clone(),
+            _ => ReadError {
+                offset: self.offset,
+                kind,
+            },
+        };
+        self.state = ReaderState::Failed(err.clone());
+        err
+    }
+}
+
+/// reads all ranges separated by commas and/or linebreaks
+fn read_ranges(input: &str, base: u32) -> Result<Vec<ProductRange>, ReadError> {
+    let mut reader = Reader::new().with_base(base);
+    let mut ranges = vec![];
+    for symbol in input.chars() {
+        if let Some(products) = reader.read(symbol)? {
+            ranges.push(products);
+        }
+    }
+    if let Some(products) = reader.finish()? {
+        ranges.push(products);
+    }
+    Ok(ranges)
+}
+
+const DECIMAL: u32 = 10;
+
+/// number of digits in the given base, counted exactly (zero has one digit)
+fn number_length(num: u128, base: u32) -> u32 {
+    match num.checked_ilog(base as u128) {
+        Some(exponent) => exponent + 1,
+        None => 1,
+    }
+}
+
+/// panics beyond the `u128` range (e.g. exponents > 38 in base 10)
+fn power(base: u32, exponent: u32) -> u128 {
+    (base as u128).pow(exponent)
 }
 
-fn number_length(num: i64) -> i64 {
-    (num as f64).log10() as i64 + 1
+/// `1 + base^segment_length + base^(2 * segment_length) + ...` with `repetitions` terms,
+/// every id repeating a segment is a multiple of it
+fn repeater(segment_length: u32, repetitions: u32, base: u32) -> u128 {
+    (1..repetitions).fold(1, |factor, reps| {
+        factor + power(base, segment_length * reps)
+    })
 }
 
-const POWERS: [i64; 11] = [
-    1,
-    10,
-    100,
-    1000,
-    10000,
-    100000,
-    1000000,
-    10000000,
-    100000000,
-    1000000000,
-    10000000000,
-];
-fn pow10(exponent: i64) -> i64 {
-    POWERS[exponent as usize]
+/// writes a number with digits `0-9a-z` of the given base
+fn format_in_base(mut num: u128, base: u32) -> String {
+    let mut digits = vec![];
+    loop {
+        digits.push(char::from_digit((num % base as u128) as u32, base).unwrap());
+        num /= base as u128;
+        if num == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
 }
 
-fn is_valid_product_part1(id: i64) -> bool {
-    let length = number_length(id);
-    if length % 2 != 0 {
+#[cfg(test)]
+fn is_valid_product_part1(id: u128, base: u32) -> bool {
+    let length = number_length(id, base);
+    if !length.is_multiple_of(2) {
         return true;
     }
-    let factor = pow10(length / 2) + 1;
 
-    id % factor != 0
+    !id.is_multiple_of(repeater(length / 2, 2, base))
 }
 
-fn find_invalid_products_part1(products: &ProductRange) -> Vec<i64> {
-    let mut result: Vec<i64> = vec![];
+#[cfg(test)]
+fn find_invalid_products_part1(products: &ProductRange, base: u32) -> Vec<u64> {
+    let mut result: Vec<u64> = vec![];
 
     for id in products.start..=products.end {
-        if !is_valid_product_part1(id) {
+        if !is_valid_product_part1(id as u128, base) {
             result.push(id);
         }
     }
@@ -136,35 +364,29 @@ fn find_invalid_products_part1(products: &ProductRange) -> Vec<i64> {
     result
 }
 
-fn is_valid_product_part2(id: i64) -> bool {
-    let length = number_length(id);
+#[cfg(test)]
+fn is_valid_product_part2(id: u128, base: u32) -> bool {
+    let length = number_length(id, base);
 
-    for segment_length in (1..7).rev() {
-        if length % segment_length != 0 {
+    for segment_length in (1..length).rev() {
+        if !length.is_multiple_of(segment_length) {
             continue;
         }
         let repetitions = length / segment_length;
-        if repetitions <= 1 {
-            continue;
-        }
-
-        let mut factor = 1;
-        for reps in 1..repetitions {
-            factor += pow10(segment_length * reps);
-        }
 
-        if id % factor == 0 {
+        if id.is_multiple_of(repeater(segment_length, repetitions, base)) {
             return false;
         }
     }
     true
 }
 
-fn find_invalid_products_part2(products: &ProductRange) -> Vec<i64> {
-    let mut result: Vec<i64> = vec![];
+#[cfg(test)]
+fn find_invalid_products_part2(products: &ProductRange, base: u32) -> Vec<u64> {
+    let mut result: Vec<u64> = vec![];
 
     for id in products.start..=products.end {
-        if !is_valid_product_part2(id) {
+        if !is_valid_product_part2(id as u128, base) {
             result.push(id);
         }
     }
@@ -172,27 +394,382 @@ fn find_invalid_products_part2(products: &ProductRange) -> Vec<i64> {
     result
 }
 
-fn main() {
-    let mut reader = Reader::new();
-    let mut invalid_products_part1 = 0;
-    let mut invalid_products_part2 = 0;
+/// sums all ids within `products` that consist of a block of `block_length` digits
+/// (without leading zero) repeated `repetitions` times.
+///
+/// every such id is `block * repeater(block_length, repetitions, base)`,
+/// so the blocks form a consecutive range and the ids sum up arithmetically
+fn sum_repeated_blocks(
+    products: &ProductRange,
+    block_length: u32,
+    repetitions: u32,
+    base: u32,
+) -> i128 {
+    let repeater = repeater(block_length, repetitions, base) as i128;
+    let blocks = repeated_block_range(products, block_length, repetitions, base);
+    if blocks.is_empty() {
+        return 0;
+    }
+    let first_block = *blocks.start() as i128;
+    let last_block = *blocks.end() as i128;
+    repeater * (first_block + last_block) * (last_block - first_block + 1) / 2
+}
 
-    let file = File::open("input.txt").unwrap();
-    let fileio = BufReader::new(file);
+/// blocks of `block_length` digits (without leading zero), which repeated
+/// `repetitions` times give an id within `products`
+fn repeated_block_range(
+    products: &ProductRange,
+    block_length: u32,
+    repetitions: u32,
+    base: u32,
+) -> RangeInclusive<u128> {
+    let block_power = power(base, block_length);
+    let repeater = repeater(block_length, repetitions, base);
 
-    for input in fileio.bytes() {
-        let checked_input = input.ok().unwrap();
-        let event = reader.read(checked_input as char);
-        if let Some(products) = event {
-            let new_invalid_products_part1 = find_invalid_products_part1(&products);
-            for id in new_invalid_products_part1 {
-                invalid_products_part1 += id;
+    let first_block = max(
+        block_power / base as u128,
+        (products.start as u128).div_ceil(repeater),
+    );
+    let last_block = min(block_power - 1, products.end as u128 / repeater);
+    first_block..=last_block
+}
+
+/// number of digits of the largest id to consider in `products`
+fn max_id_length(products: &ProductRange, base: u32) -> u32 {
+    number_length(products.end as u128, base)
+}
+
+/// which repetitions make an id invalid
+#[derive(Clone, Copy)]
+enum RepetitionRule {
+    /// a block of digits repeated twice (part 1)
+    Twice,
+    /// a block of digits repeated N times with N > 1 (part 2)
+    NTimes,
+}
+
+impl RepetitionRule {
+    /// lengths of the blocks that may repeat within an id of `length` digits, ascending
+    pub fn block_lengths(&self, length: u32) -> Vec<u32> {
+        match self {
+            RepetitionRule::Twice if length.is_multiple_of(2) => vec![length / 2],
+            RepetitionRule::Twice => vec![],
+            RepetitionRule::NTimes => (1..length)
+                .filter(|block_length| length.is_multiple_of(*block_length))
+                .collect(),
+        }
+    }
+}
+
+/// ids of one length repeating blocks of one length, in ascending order
+struct BlockStream {
+    repeater: u128,
+    blocks: RangeInclusive<u128>,
+}
+
+impl BlockStream {
+    fn peek(&self) -> Option<u128> {
+        if self.blocks.is_empty() {
+            None
+        } else {
+            Some(*self.blocks.start() * self.repeater)
+        }
+    }
+}
+
+/// iterator over the invalid ids of a `ProductRange`, see `ProductRange::invalid_ids`.
+///
+/// ids are generated length by length; within a length every possible block
+/// length contributes an ascending stream of ids, which are merged (dropping
+/// duplicates like `111111`) by always taking the smallest head
+struct InvalidIds {
+    rule: RepetitionRule,
+    base: u32,
+    products: ProductRange,
+    length: u32,
+    streams: Vec<BlockStream>,
+}
+
+impl InvalidIds {
+    /// considers ids written in the given base (2 to 36)
+    pub fn in_base(mut self, base: u32) -> Self {
+        self.base = base;
+        self
+    }
+}
+
+impl Iterator for InvalidIds {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let smallest = self.streams.iter().filter_map(BlockStream::peek).min();
+            if let Some(id) = smallest {
+                for stream in &mut self.streams {
+                    if stream.peek() == Some(id) {
+                        stream.blocks.next();
+                    }
+                }
+                return Some(id as u64);
+            }
+
+            if self.products.start > self.products.end
+                || self.length >= max_id_length(&self.products, self.base)
+            {
+                return None;
             }
-            let new_invalid_products_part2 = find_invalid_products_part2(&products);
-            for id in new_invalid_products_part2 {
-                invalid_products_part2 += id;
+            self.length += 1;
+            self.streams = self
+                .rule
+                .block_lengths(self.length)
+                .into_iter()
+                .map(|block_length| {
+                    let repetitions = self.length / block_length;
+                    BlockStream {
+                        repeater: repeater(block_length, repetitions, self.base),
+                        blocks: repeated_block_range(
+                            &self.products,
+                            block_length,
+                            repetitions,
+                            self.base,
+                        ),
+                    }
+                })
+                .collect();
+        }
+    }
+}
+
+/// sums the ids within `products` that are a block of digits repeated twice
+fn sum_invalid_products_part1(products: &ProductRange, base: u32) -> i128 {
+    (1..=max_id_length(products, base) / 2)
+        .map(|block_length| sum_repeated_blocks(products, block_length, 2, base))
+        .sum()
+}
+
+/// Möbius function, -1 or 1 for square-free numbers with an odd or even
+/// count of prime factors, 0 otherwise
+fn mobius(mut number: u32) -> i128 {
+    let mut result = 1;
+    let mut factor = 2;
+    while factor * factor <= number {
+        if number.is_multiple_of(factor) {
+            number /= factor;
+            if number.is_multiple_of(factor) {
+                return 0;
             }
+            result = -result;
         }
+        factor += 1;
+    }
+    if number > 1 {
+        result = -result;
+    }
+    result
+}
+
+/// sums the ids within `products` that are a block of digits repeated N times (N > 1).
+///
+/// an id like `111111` repeats blocks of length 1, 2 and 3, so the sums per block
+/// length overlap. For a given id length, ids repeating a block `d * e` times also
+/// repeat a block `d` times (`111111` = "1" x6 = "11" x3 = "111" x2); inclusion-exclusion
+/// over the repetitions `d` (weighted by the Möbius function) counts each id once.
+fn sum_invalid_products_part2(products: &ProductRange, base: u32) -> i128 {
+    let mut sum = 0;
+    for length in 2..=max_id_length(products, base) {
+        for repetitions in 2..=length {
+            if !length.is_multiple_of(repetitions) {
+                continue;
+            }
+            let weight = -mobius(repetitions);
+            if weight != 0 {
+                sum +=
+                    weight * sum_repeated_blocks(products, length / repetitions, repetitions, base);
+            }
+        }
+    }
+    sum
+}
+
+/// an invalid id, made of its shortest repeating block
+#[derive(Debug, PartialEq)]
+struct InvalidProduct {
+    id: u64,
+    block: u64,
+    repetitions: u32,
+    /// number of digits of the repeating block
+    period: u32,
+}
+
+/// splits an invalid id into its shortest repeating block allowed by `rule`
+///
+/// panics if the id does not repeat any block
+fn shortest_repetition(id: u64, rule: RepetitionRule, base: u32) -> InvalidProduct {
+    let length = number_length(id as u128, base);
+    for block_length in rule.block_lengths(length) {
+        let repetitions = length / block_length;
+        let repeater = repeater(block_length, repetitions, base);
+        if (id as u128).is_multiple_of(repeater) {
+            return InvalidProduct {
+                id,
+                block: (id as u128 / repeater) as u64,
+                repetitions,
+                period: block_length,
+            };
+        }
+    }
+    panic!("id {} does not repeat a block", id)
+}
+
+/// lists the invalid ids within `products` in ascending order,
+/// each with its shortest repeating block
+fn classify_invalid_products(
+    products: &ProductRange,
+    rule: RepetitionRule,
+    base: u32,
+) -> Vec<InvalidProduct> {
+    products
+        .invalid_ids(rule)
+        .in_base(base)
+        .map(|id| shortest_repetition(id, rule, base))
+        .collect()
+}
+
+/// counts the invalid ids per period (number of digits of the repeating block)
+fn period_histogram(invalid_products: &[InvalidProduct]) -> BTreeMap<u32, usize> {
+    let mut histogram = BTreeMap::new();
+    for invalid_product in invalid_products {
+        *histogram.entry(invalid_product.period).or_insert(0) += 1;
+    }
+    histogram
+}
+
+fn histogram_to_json(histogram: &BTreeMap<u32, usize>) -> String {
+    let entries: Vec<String> = histogram
+        .iter()
+        .map(|(period, count)| format!("\"{}\":{}", period, count))
+        .collect();
+    format!("{{{}}}", entries.join(","))
+}
+
+/// JSON report of every range with its invalid ids,
+/// a histogram per range and a histogram over all ranges.
+/// ids are numbers, blocks and descriptions are written in the given base
+fn invalid_products_report(ranges: &[ProductRange], rule: RepetitionRule, base: u32) -> String {
+    let mut total_histogram = BTreeMap::<u32, usize>::new();
+    let mut range_reports: Vec<String> = vec![];
+
+    for products in ranges {
+        let invalid_products = classify_invalid_products(products, rule, base);
+        let histogram = period_histogram(&invalid_products);
+        for (period, count) in &histogram {
+            *total_histogram.entry(*period).or_insert(0) += count;
+        }
+
+        let invalid_ids: Vec<String> = invalid_products
+            .iter()
+            .map(|invalid_product| {
+                format!(
+                    "{{\"id\":{},\"block\":\"{}\",\"repetitions\":{},\"description\":\"{} = \\\"{}\\\" x{}\"}}",
+                    invalid_product.id,
+                    format_in_base(invalid_product.block as u128, base),
+                    invalid_product.repetitions,
+                    format_in_base(invalid_product.id as u128, base),
+                    format_in_base(invalid_product.block as u128, base),
+                    invalid_product.repetitions
+                )
+            })
+            .collect();
+        range_reports.push(format!(
+            "{{\"start\":{},\"end\":{},\"invalid_ids\":[{}],\"histogram\":{}}}",
+            products.start,
+            products.end,
+            invalid_ids.join(","),
+            histogram_to_json(&histogram)
+        ));
+    }
+
+    format!(
+        "{{\"ranges\":[{}],\"histogram\":{}}}",
+        range_reports.join(","),
+        histogram_to_json(&total_histogram)
+    )
+}
+
+enum Mode {
+    /// print the sums of both parts
+    Sums,
+    /// list overlapping ranges before printing the sums
+    ReportOverlaps,
+    /// print a JSON report of all invalid ids instead of the sums
+    ReportInvalidIds(RepetitionRule),
+}
+
+/// removes `--base=<base>` from the arguments and returns the base
+/// ids are written in, by default decimal
+fn parse_base(args: &mut Vec<String>) -> u32 {
+    let mut base = DECIMAL;
+    args.retain(|arg| match arg.strip_prefix("--base=") {
+        Some(value) => {
+            base = match value.parse::<u32>() {
+                Ok(value) if (2..=36).contains(&value) => value,
+                _ => panic!("unsupported base >{}<, expected 2 to 36", value),
+            };
+            false
+        }
+        None => true,
+    });
+    base
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let base = parse_base(&mut args);
+    let mode = match args.first().map(String::as_str) {
+        None => Mode::Sums,
+        Some("--report-overlaps") => Mode::ReportOverlaps,
+        Some("--report") => Mode::ReportInvalidIds(RepetitionRule::NTimes),
+        Some("--report-part1") => Mode::ReportInvalidIds(RepetitionRule::Twice),
+        Some(arg) => panic!(
+            "unknown argument >{}<, expected --report-overlaps, --report or --report-part1",
+            arg
+        ),
+    };
+    let code = match std::fs::read_to_string("input.txt") {
+        Ok(file) => file,
+        Err(err) => panic!("Could not read file: {}", err),
+    };
+    let ranges = match read_ranges(&code, base) {
+        Ok(ranges) => ranges,
+        Err(err) => panic!("Could not read ranges: {}", err),
+    };
+
+    if let Mode::ReportInvalidIds(rule) = mode {
+        println!("{}", invalid_products_report(&ranges, rule, base));
+        return;
+    }
+
+    if let Mode::ReportOverlaps = mode {
+        println!("== Overlapping ranges ==");
+        for (first, second) in find_overlaps(&ranges) {
+            println!(
+                "range #{} ({}-{}) overlaps range #{} ({}-{})",
+                first + 1,
+                format_in_base(ranges[first].start as u128, base),
+                format_in_base(ranges[first].end as u128, base),
+                second + 1,
+                format_in_base(ranges[second].start as u128, base),
+                format_in_base(ranges[second].end as u128, base)
+            );
+        }
+        println!();
+    }
+
+    let mut invalid_products_part1 = 0;
+    let mut invalid_products_part2 = 0;
+    for products in merge_ranges(ranges) {
+        invalid_products_part1 += sum_invalid_products_part1(&products, base);
+        invalid_products_part2 += sum_invalid_products_part2(&products, base);
     }
     println!("== Part 1 ==");
     println!("sum of invalid product ids: {}", invalid_products_part1);
@@ -205,26 +782,54 @@ fn main() {
 #[allow(non_snake_case)]
 mod tests {
     use crate::{
-        ProductRange, Reader, find_invalid_products_part1, is_valid_product_part1, read_end,
+        DECIMAL, InvalidProduct, ProductRange, ReadError, ReadErrorKind, Reader, RepetitionRule,
+        classify_invalid_products, find_invalid_products_part1, find_invalid_products_part2,
+        find_overlaps, format_in_base, invalid_products_report, is_valid_product_part1,
+        is_valid_product_part2, merge_ranges, mobius, number_length, period_histogram, power,
+        read_end, read_ranges, sum_invalid_products_part1, sum_invalid_products_part2,
     };
 
+    fn assert_sums_match_brute_force(products: &ProductRange) {
+        let sum = |ids: Vec<u64>| ids.iter().map(|&id| id as i128).sum::<i128>();
+        assert_eq!(
+            sum(find_invalid_products_part1(products, DECIMAL)),
+            sum_invalid_products_part1(products, DECIMAL)
+        );
+        assert_eq!(
+            sum(find_invalid_products_part2(products, DECIMAL)),
+            sum_invalid_products_part2(products, DECIMAL)
+        );
+    }
+
+    /// small xorshift generator, good enough to produce test ranges
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
     #[test]
     fn is_invalid_product_id__detects_invalid_products() {
-        assert!(!is_valid_product_part1(22));
-        assert!(!is_valid_product_part1(2020));
-        assert!(!is_valid_product_part1(123123));
+        assert!(!is_valid_product_part1(22, DECIMAL));
+        assert!(!is_valid_product_part1(2020, DECIMAL));
+        assert!(!is_valid_product_part1(123123, DECIMAL));
     }
 
     #[test]
     fn find_invalid_products_part1__when_called__finds_expected_invalid_ids() {
         assert!(
-            find_invalid_products_part1(&crate::ProductRange { start: 10, end: 25 }).len() == 2
+            find_invalid_products_part1(&crate::ProductRange { start: 10, end: 25 }, DECIMAL).len()
+                == 2
         );
         assert!(
-            find_invalid_products_part1(&crate::ProductRange {
-                start: 1000,
-                end: 1234
-            })
+            find_invalid_products_part1(
+                &crate::ProductRange {
+                    start: 1000,
+                    end: 1234
+                },
+                DECIMAL
+            )
             .len()
                 == 3
         );
@@ -236,7 +841,7 @@ mod tests {
         let end = "1011".to_string();
         let mut result = Option::<ProductRange>::None;
 
-        let _state = read_end(',', start, end, &mut result);
+        let _state = read_end(',', start, end, false, DECIMAL, &mut result);
 
         assert!(result.is_some());
     }
@@ -247,7 +852,7 @@ mod tests {
         let end = "1011".to_string();
         let mut result = Option::<ProductRange>::None;
 
-        let _state = read_end('\n', start, end, &mut result);
+        let _state = read_end('\n', start, end, false, DECIMAL, &mut result);
 
         assert!(result.is_some());
     }
@@ -260,7 +865,7 @@ mod tests {
         let mut count = 0;
 
         for input in inputstream.chars() {
-            let result = reader.read(input);
+            let result = reader.read(input).unwrap();
             if let Some(ids) = result {
                 count += 1;
 
@@ -271,4 +876,597 @@ mod tests {
 
         assert!(count == 1);
     }
+
+    #[test]
+    fn Reader_read__after_error__returns_error_again() {
+        let mut reader = Reader::new();
+        for input in "12-x".chars().take(3) {
+            reader.read(input).unwrap();
+        }
+        let err = ReadError {
+            offset: 3,
+            kind: ReadErrorKind::UnexpectedCharacter('x'),
+        };
+        assert_eq!(Err(err.clone()), reader.read('x'));
+        assert_eq!(Err(err.clone()), reader.read('5'));
+        assert_eq!(Err(err), reader.finish());
+    }
+
+    #[test]
+    fn mobius__for_small_numbers__matches_known_values() {
+        let expected = [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0];
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(*value, mobius(index as u32 + 1));
+        }
+    }
+
+    #[test]
+    fn sum_invalid_products_part2__for_id_with_several_periods__counts_it_once() {
+        let products = ProductRange {
+            start: 111111,
+            end: 111111,
+        };
+        assert_eq!(111111, sum_invalid_products_part1(&products, DECIMAL));
+        assert_eq!(111111, sum_invalid_products_part2(&products, DECIMAL));
+    }
+
+    #[test]
+    fn sum_invalid_products__for_random_ranges__match_brute_force() {
+        let mut state = 0x2025_0102;
+        for _ in 0..200 {
+            let length = 1 + next_random(&mut state) % 7;
+            let start = next_random(&mut state) % 10_u64.pow(length as u32) + 1;
+            let end = start + next_random(&mut state) % 20000;
+            assert_sums_match_brute_force(&ProductRange { start, end });
+        }
+    }
+
+    #[test]
+    fn sum_invalid_products__for_huge_range__sums_without_scanning() {
+        let products = ProductRange {
+            start: 1,
+            end: 9_999_999_999,
+        };
+        /* part 1: every block of 1..=5 digits, times its repeater 10^p + 1 */
+        let expected_part1: i128 = (1..=5)
+            .map(|p| {
+                let first = 10_i128.pow(p - 1);
+                let last = 10_i128.pow(p) - 1;
+                (10_i128.pow(p) + 1) * (first + last) * (last - first + 1) / 2
+            })
+            .sum();
+        assert_eq!(
+            expected_part1,
+            sum_invalid_products_part1(&products, DECIMAL)
+        );
+        /* part 2: every distinct id built from a block repeated N times */
+        let mut invalid_ids = std::collections::HashSet::new();
+        for length in 2..=10_u32 {
+            for block_length in (1..length).filter(|p| length.is_multiple_of(*p)) {
+                for block in 10_i128.pow(block_length - 1)..10_i128.pow(block_length) {
+                    let id = (0..length / block_length)
+                        .fold(0, |id, _| id * 10_i128.pow(block_length) + block);
+                    invalid_ids.insert(id);
+                }
+            }
+        }
+        let expected_part2: i128 = invalid_ids.iter().sum();
+        assert_eq!(
+            expected_part2,
+            sum_invalid_products_part2(&products, DECIMAL)
+        );
+    }
+
+    #[test]
+    fn number_length__at_every_power_of_ten__counts_exactly() {
+        assert_eq!(1, number_length(0, DECIMAL));
+        for exponent in 1..=38 {
+            assert_eq!(
+                exponent,
+                number_length(power(DECIMAL, exponent) - 1, DECIMAL)
+            );
+            assert_eq!(
+                exponent + 1,
+                number_length(power(DECIMAL, exponent), DECIMAL)
+            );
+        }
+        assert_eq!(39, number_length(u128::MAX, DECIMAL));
+    }
+
+    #[test]
+    fn is_valid_product__at_every_power_of_ten__detects_repeated_nines() {
+        for exponent in 1..=38 {
+            let nines = power(DECIMAL, exponent) - 1;
+            assert_eq!(exponent % 2 != 0, is_valid_product_part1(nines, DECIMAL));
+            assert_eq!(exponent == 1, is_valid_product_part2(nines, DECIMAL));
+            assert!(is_valid_product_part1(power(DECIMAL, exponent), DECIMAL));
+            assert!(is_valid_product_part2(power(DECIMAL, exponent), DECIMAL));
+        }
+    }
+
+    #[test]
+    fn is_valid_product_part2__for_long_segments__detects_repetition() {
+        let block = 1234567890123_u128;
+        assert!(!is_valid_product_part1(
+            block * (power(DECIMAL, 13) + 1),
+            DECIMAL
+        ));
+        assert!(!is_valid_product_part2(
+            block * (power(DECIMAL, 13) + 1),
+            DECIMAL
+        ));
+        assert!(!is_valid_product_part2(
+            block * (power(DECIMAL, 26) + power(DECIMAL, 13) + 1),
+            DECIMAL
+        ));
+        assert!(is_valid_product_part2(
+            block * (power(DECIMAL, 26) + power(DECIMAL, 13) + 1) + 1,
+            DECIMAL
+        ));
+    }
+
+    #[test]
+    fn sum_invalid_products__around_every_power_of_ten__match_brute_force() {
+        for exponent in 1..=19 {
+            let power = 10_u64.pow(exponent);
+            assert_sums_match_brute_force(&ProductRange {
+                start: power.saturating_sub(1000),
+                end: power + 1000,
+            });
+        }
+        assert_sums_match_brute_force(&ProductRange {
+            start: u64::MAX - 2000,
+            end: u64::MAX,
+        });
+        /* 18446744071844674407 is the largest u64 repeating a block twice */
+        assert_sums_match_brute_force(&ProductRange {
+            start: 18446744071844674407 - 1000,
+            end: 18446744071844674407 + 1000,
+        });
+    }
+
+    #[test]
+    fn merge_ranges__given_overlapping_ranges__merges_them() {
+        let ranges = vec![
+            ProductRange { start: 50, end: 60 },
+            ProductRange { start: 10, end: 25 },
+            ProductRange { start: 20, end: 30 },
+            ProductRange { start: 30, end: 40 },
+            ProductRange { start: 55, end: 58 },
+        ];
+        assert_eq!(
+            vec![
+                ProductRange { start: 10, end: 40 },
+                ProductRange { start: 50, end: 60 }
+            ],
+            merge_ranges(ranges)
+        );
+    }
+
+    #[test]
+    fn find_overlaps__given_overlapping_ranges__reports_pairs_in_input_order() {
+        let ranges = vec![
+            ProductRange { start: 50, end: 60 },
+            ProductRange { start: 10, end: 25 },
+            ProductRange { start: 20, end: 30 },
+            ProductRange { start: 31, end: 40 },
+            ProductRange { start: 55, end: 58 },
+        ];
+        assert_eq!(vec![(0, 4), (1, 2)], find_overlaps(&ranges));
+    }
+
+    #[test]
+    fn merge_ranges__given_overlapping_ranges__sums_distinct_ids() {
+        let ranges = vec![
+            ProductRange { start: 10, end: 30 },
+            ProductRange { start: 20, end: 40 },
+        ];
+        let sum: i128 = merge_ranges(ranges)
+            .iter()
+            .map(|products| sum_invalid_products_part1(products, DECIMAL))
+            .sum();
+        assert_eq!(11 + 22 + 33, sum);
+    }
+
+    #[test]
+    fn classify_invalid_products__for_id_with_several_periods__keeps_shortest_block() {
+        let products = ProductRange {
+            start: 111110,
+            end: 123123,
+        };
+        let invalid_products =
+            classify_invalid_products(&products, RepetitionRule::NTimes, DECIMAL);
+        assert_eq!(
+            InvalidProduct {
+                id: 111111,
+                block: 1,
+                repetitions: 6,
+                period: 1
+            },
+            invalid_products[0]
+        );
+        assert_eq!(
+            Some(&InvalidProduct {
+                id: 123123,
+                block: 123,
+                repetitions: 2,
+                period: 3
+            }),
+            invalid_products.last()
+        );
+        let histogram = period_histogram(&invalid_products);
+        assert_eq!(Some(&1), histogram.get(&1));
+        assert_eq!(Some(&1), histogram.get(&2));
+        assert_eq!(Some(&12), histogram.get(&3));
+    }
+
+    #[test]
+    fn classify_invalid_products__for_random_ranges__match_brute_force() {
+        let mut state = 0x2025_0110;
+        for _ in 0..100 {
+            let length = 1 + next_random(&mut state) % 7;
+            let start = next_random(&mut state) % 10_u64.pow(length as u32) + 1;
+            let end = start + next_random(&mut state) % 20000;
+            let products = ProductRange { start, end };
+
+            let invalid_products =
+                classify_invalid_products(&products, RepetitionRule::NTimes, DECIMAL);
+            let ids: Vec<u64> = invalid_products.iter().map(|product| product.id).collect();
+            assert_eq!(find_invalid_products_part2(&products, DECIMAL), ids);
+            for product in &invalid_products {
+                assert_eq!(
+                    product.id.to_string(),
+                    product
+                        .block
+                        .to_string()
+                        .repeat(product.repetitions as usize)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_products_report__for_ranges__writes_json() {
+        let ranges = vec![
+            ProductRange {
+                start: 95,
+                end: 115,
+            },
+            ProductRange { start: 10, end: 12 },
+        ];
+        assert_eq!(
+            concat!(
+                "{\"ranges\":[",
+                "{\"start\":95,\"end\":115,\"invalid_ids\":[",
+                "{\"id\":99,\"block\":\"9\",\"repetitions\":2,\"description\":\"99 = \\\"9\\\" x2\"},",
+                "{\"id\":111,\"block\":\"1\",\"repetitions\":3,\"description\":\"111 = \\\"1\\\" x3\"}",
+                "],\"histogram\":{\"1\":2}},",
+                "{\"start\":10,\"end\":12,\"invalid_ids\":[",
+                "{\"id\":11,\"block\":\"1\",\"repetitions\":2,\"description\":\"11 = \\\"1\\\" x2\"}",
+                "],\"histogram\":{\"1\":1}}",
+                "],\"histogram\":{\"1\":3}}"
+            ),
+            invalid_products_report(&ranges, RepetitionRule::NTimes, DECIMAL)
+        );
+    }
+
+    #[test]
+    fn classify_invalid_products__for_rule_twice__splits_into_halves() {
+        let products = ProductRange {
+            start: 111111,
+            end: 111111,
+        };
+        assert_eq!(
+            vec![InvalidProduct {
+                id: 111111,
+                block: 111,
+                repetitions: 2,
+                period: 3
+            }],
+            classify_invalid_products(&products, RepetitionRule::Twice, DECIMAL)
+        );
+    }
+
+    #[test]
+    fn ProductRange_invalid_ids__for_random_ranges__match_brute_force() {
+        let mut state = 0x2025_0111;
+        for _ in 0..100 {
+            let length = 1 + next_random(&mut state) % 7;
+            let start = next_random(&mut state) % 10_u64.pow(length as u32) + 1;
+            let end = start + next_random(&mut state) % 20000;
+            let products = ProductRange { start, end };
+
+            assert_eq!(
+                find_invalid_products_part1(&products, DECIMAL),
+                products
+                    .invalid_ids(RepetitionRule::Twice)
+                    .collect::<Vec<u64>>()
+            );
+            assert_eq!(
+                find_invalid_products_part2(&products, DECIMAL),
+                products
+                    .invalid_ids(RepetitionRule::NTimes)
+                    .collect::<Vec<u64>>()
+            );
+        }
+    }
+
+    #[test]
+    fn ProductRange_invalid_ids__for_huge_range__yields_lazily() {
+        let products = ProductRange {
+            start: 1,
+            end: u64::MAX,
+        };
+        assert_eq!(
+            vec![11, 22, 33],
+            products
+                .invalid_ids(RepetitionRule::Twice)
+                .take(3)
+                .collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            vec![11, 22, 33, 44, 55, 66, 77, 88, 99, 111, 222],
+            products
+                .invalid_ids(RepetitionRule::NTimes)
+                .take(11)
+                .collect::<Vec<u64>>()
+        );
+        let ten_digits = ProductRange {
+            start: 1_000_000_000,
+            end: 9_999_999_999,
+        };
+        assert_eq!(90000, ten_digits.invalid_ids(RepetitionRule::Twice).count());
+    }
+
+    #[test]
+    fn ProductRange_invalid_ids__summed__match_closed_form() {
+        let products = ProductRange {
+            start: 1,
+            end: 99_999_999,
+        };
+        let sum_part1: u128 = products
+            .invalid_ids(RepetitionRule::Twice)
+            .map(|id| id as u128)
+            .sum();
+        let sum_part2: u128 = products
+            .invalid_ids(RepetitionRule::NTimes)
+            .map(|id| id as u128)
+            .sum();
+        assert_eq!(
+            sum_invalid_products_part1(&products, DECIMAL),
+            sum_part1 as i128
+        );
+        assert_eq!(
+            sum_invalid_products_part2(&products, DECIMAL),
+            sum_part2 as i128
+        );
+    }
+
+    #[test]
+    fn ProductRange_invalid_ids__for_empty_range__yields_nothing() {
+        let products = ProductRange { start: 30, end: 20 };
+        assert_eq!(0, products.invalid_ids(RepetitionRule::NTimes).count());
+    }
+
+    #[test]
+    fn read_ranges__with_mixed_separators_and_whitespace__reads_all_ranges() {
+        let ranges = read_ranges(
+            " 11-22, 95 - 115,\r\n998-1012\n\n1188511880-1188511890,\n",
+            DECIMAL,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                ProductRange { start: 11, end: 22 },
+                ProductRange {
+                    start: 95,
+                    end: 115
+                },
+                ProductRange {
+                    start: 998,
+                    end: 1012
+                },
+                ProductRange {
+                    start: 1188511880,
+                    end: 1188511890
+                },
+            ],
+            ranges
+        );
+    }
+
+    #[test]
+    fn read_ranges__without_final_separator__keeps_last_range() {
+        assert_eq!(
+            vec![
+                ProductRange { start: 11, end: 22 },
+                ProductRange { start: 30, end: 40 }
+            ],
+            read_ranges("11-22\n30-40", DECIMAL).unwrap()
+        );
+    }
+
+    #[test]
+    fn read_ranges__given_start_after_end__reports_error() {
+        assert_eq!(
+            Err(ReadError {
+                offset: 11,
+                kind: ReadErrorKind::StartAfterEnd { start: 40, end: 30 }
+            }),
+            read_ranges("11-22,40-30,50-60", DECIMAL)
+        );
+    }
+
+    #[test]
+    fn read_ranges__given_invalid_input__reports_byte_offset() {
+        assert_eq!(
+            Err(ReadError {
+                offset: 4,
+                kind: ReadErrorKind::UnexpectedCharacter('x')
+            }),
+            read_ranges("11-2x2", DECIMAL)
+        );
+        assert_eq!(
+            Err(ReadError {
+                offset: 5,
+                kind: ReadErrorKind::UnexpectedCharacter('2')
+            }),
+            read_ranges("11-2 2", DECIMAL)
+        );
+        assert_eq!(
+            Err(ReadError {
+                offset: 3,
+                kind: ReadErrorKind::MissingNumber
+            }),
+            read_ranges("11-,", DECIMAL)
+        );
+        assert_eq!(
+            Err(ReadError {
+                offset: 0,
+                kind: ReadErrorKind::MissingNumber
+            }),
+            read_ranges("-22", DECIMAL)
+        );
+        assert_eq!(
+            Err(ReadError {
+                offset: 2,
+                kind: ReadErrorKind::MissingNumber
+            }),
+            read_ranges("11", DECIMAL)
+        );
+        assert_eq!(
+            Err(ReadError {
+                offset: 22,
+                kind: ReadErrorKind::NumberOutOfRange
+            }),
+            read_ranges("1-99999999999999999999,", DECIMAL)
+        );
+    }
+
+    /// string based reference: writes the id in the base and compares the blocks
+    fn repeats_block_reference(id: u64, base: u32, only_twice: bool) -> bool {
+        let mut digits = String::new();
+        let mut rest = id;
+        loop {
+            digits.insert(
+                0,
+                "0123456789abcdefghijklmnopqrstuvwxyz".as_bytes()[(rest % base as u64) as usize]
+                    as char,
+            );
+            rest /= base as u64;
+            if rest == 0 {
+                break;
+            }
+        }
+        (1..digits.len())
+            .filter(|block_length| digits.len().is_multiple_of(*block_length))
+            .filter(|block_length| !only_twice || digits.len() == 2 * block_length)
+            .any(|block_length| {
+                digits[..block_length].repeat(digits.len() / block_length) == digits
+            })
+    }
+
+    #[test]
+    fn format_in_base__for_common_bases__writes_digits() {
+        assert_eq!("0", format_in_base(0, 2));
+        assert_eq!("101010", format_in_base(42, 2));
+        assert_eq!("777", format_in_base(511, 8));
+        assert_eq!("ff", format_in_base(255, 16));
+        assert_eq!("zz", format_in_base(1295, 36));
+        assert_eq!(64, number_length(u64::MAX as u128, 2));
+        assert_eq!(16, number_length(u64::MAX as u128, 16));
+    }
+
+    #[test]
+    fn is_valid_product__in_other_bases__matches_string_reference() {
+        let mut state = 0x2025_0113;
+        for base in [2, 8, 16, 36] {
+            for _ in 0..2000 {
+                let id = next_random(&mut state) >> (next_random(&mut state) % 64);
+                assert_eq!(
+                    !repeats_block_reference(id, base, true),
+                    is_valid_product_part1(id as u128, base)
+                );
+                assert_eq!(
+                    !repeats_block_reference(id, base, false),
+                    is_valid_product_part2(id as u128, base)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn range_scans__in_other_bases__match_string_reference() {
+        let mut state = 0x2025_0213;
+        for base in [2, 8, 16, 36] {
+            for _ in 0..50 {
+                let start = next_random(&mut state) >> (next_random(&mut state) % 64);
+                let end = start.saturating_add(next_random(&mut state) % 5000);
+                let products = ProductRange { start, end };
+
+                let expected_part1: Vec<u64> = (start..=end)
+                    .filter(|&id| repeats_block_reference(id, base, true))
+                    .collect();
+                let expected_part2: Vec<u64> = (start..=end)
+                    .filter(|&id| repeats_block_reference(id, base, false))
+                    .collect();
+                let sum = |ids: &Vec<u64>| ids.iter().map(|&id| id as i128).sum::<i128>();
+
+                assert_eq!(expected_part1, find_invalid_products_part1(&products, base));
+                assert_eq!(expected_part2, find_invalid_products_part2(&products, base));
+                assert_eq!(
+                    expected_part1,
+                    products
+                        .invalid_ids(RepetitionRule::Twice)
+                        .in_base(base)
+                        .collect::<Vec<u64>>()
+                );
+                assert_eq!(
+                    expected_part2,
+                    products
+                        .invalid_ids(RepetitionRule::NTimes)
+                        .in_base(base)
+                        .collect::<Vec<u64>>()
+                );
+                assert_eq!(
+                    sum(&expected_part1),
+                    sum_invalid_products_part1(&products, base)
+                );
+                assert_eq!(
+                    sum(&expected_part2),
+                    sum_invalid_products_part2(&products, base)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_ranges__in_base_16__reads_hex_digits() {
+        assert_eq!(
+            vec![ProductRange {
+                start: 0xaa,
+                end: 0xFF
+            }],
+            read_ranges("aa-FF\n", 16).unwrap()
+        );
+        assert_eq!(
+            Err(ReadError {
+                offset: 1,
+                kind: ReadErrorKind::UnexpectedCharacter('2')
+            }),
+            read_ranges("12-3", 2)
+        );
+    }
+
+    #[test]
+    fn invalid_products_report__in_base_16__writes_blocks_in_base() {
+        let ranges = vec![ProductRange {
+            start: 0xabab,
+            end: 0xabab,
+        }];
+        assert_eq!(
+            "{\"ranges\":[{\"start\":43947,\"end\":43947,\"invalid_ids\":[{\"id\":43947,\"block\":\"ab\",\"repetitions\":2,\"description\":\"abab = \\\"ab\\\" x2\"}],\"histogram\":{\"2\":1}}],\"histogram\":{\"2\":1}}",
+            invalid_products_report(&ranges, RepetitionRule::NTimes, 16)
+        );
+    }
 }
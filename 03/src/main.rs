@@ -1,28 +1,71 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 struct Extremal {
     value: i64,
     position: usize,
 }
 
-/// converts number chars '0'..'9' to their numerical value.
+const DECIMAL: u32 = 10;
+
+/// converts digit chars '0'..'9' and 'a'..'z' to their numerical value,
+/// lines are checked against their base by `read_lines` before.
 /// panics for any other character
 fn to_numerical_value(symbol: char) -> i64 {
-    match &symbol {
-        '0' => 0,
-        '1' => 1,
-        '2' => 2,
-        '3' => 3,
-        '4' => 4,
-        '5' => 5,
-        '6' => 6,
-        '7' => 7,
-        '8' => 8,
-        '9' => 9,
-        _ => panic!("non numerical symbol >{}<", symbol),
+    match symbol.to_digit(36) {
+        Some(value) => value as i64,
+        None => panic!("non numerical symbol >{}<", symbol),
     }
 }
 
+/// error of `read_lines`, pointing at line and column (both 1-based)
+/// of the first symbol that is no digit in the base
+#[derive(Debug, PartialEq)]
+struct ReadError {
+    line: usize,
+    column: usize,
+    symbol: char,
+    base: u32,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unexpected character {:?} for base {} at line {}, column {}",
+            self.symbol, self.base, self.line, self.column
+        )
+    }
+}
+
+/// returns the non-empty lines of the input, after checking that
+/// they only consist of digits in the given base
+fn read_lines(code: &str, base: u32) -> Result<Vec<&str>, ReadError> {
+    let mut lines = Vec::new();
+    for (index, line) in code.lines().enumerate() {
+        if let Some((column, symbol)) = line
+            .chars()
+            .enumerate()
+            .find(|(_, symbol)| symbol.to_digit(base).is_none())
+        {
+            return Err(ReadError {
+                line: index + 1,
+                column: column + 1,
+                symbol,
+                base,
+            });
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    Ok(lines)
+}
+
 /// given a string of decimals, it returns value and position
 /// of the first occurance of the largest digit
+#[cfg(test)]
 fn find_largest_symbol(line: &str) -> Extremal {
     let mut current_max = 0;
     let mut current_pos = 0;
@@ -40,10 +83,12 @@ fn find_largest_symbol(line: &str) -> Extremal {
     }
 }
 
-/// finds maximal power (following day 3 of Advent of Code 2025)
+/// finds maximal power by searching the largest digit in a shrinking window
+/// for every selected digit, O(n * digits); kept as reference for tests
 ///
 /// panics if requested digits exceed the ones available
-fn find_maximal_power(line: &str, digits: usize) -> i64 {
+#[cfg(test)]
+fn find_maximal_power_by_windows(line: &str, digits: usize) -> i64 {
     let length = line.len();
     let mut current_offset = 0;
     let mut current_value: i64 = 0;
@@ -58,23 +103,375 @@ fn find_maximal_power(line: &str, digits: usize) -> i64 {
     current_value
 }
 
+/// selects digits keeping them on a stack: a digit that `replaces` the one on
+/// top of the stack pops it, as long as enough digits remain to fill the
+/// selection. Every digit is pushed and popped at most once, O(n).
+///
+/// returns value and absolute position (shifted by `offset`) of every
+/// selected digit in order, panics if requested digits exceed the ones available
+fn select_by_stack(
+    line: &str,
+    digits: usize,
+    offset: usize,
+    replaces: fn(i64, i64) -> bool,
+) -> Vec<Extremal> {
+    let length = line.len();
+    if digits > length {
+        panic!("requested {} digits from a line of {}", digits, length);
+    }
+    let mut selected: Vec<Extremal> = Vec::with_capacity(digits);
+
+    for (position, symbol) in line.chars().enumerate() {
+        let value = to_numerical_value(symbol);
+        let remaining = length - position;
+        while let Some(last) = selected.last() {
+            if !replaces(value, last.value) || selected.len() - 1 + remaining < digits {
+                break;
+            }
+            selected.pop();
+        }
+        if selected.len() < digits {
+            selected.push(Extremal {
+                value,
+                position: position + offset,
+            });
+        }
+    }
+
+    selected
+}
+
+/// selects the digits forming the maximal power
+fn find_maximal_selection(line: &str, digits: usize) -> Vec<Extremal> {
+    select_by_stack(line, digits, 0, |value, last| value > last)
+}
+
+/// selects the digits forming the minimal number, leading zeros allowed
+fn find_minimal_selection(line: &str, digits: usize) -> Vec<Extremal> {
+    select_by_stack(line, digits, 0, |value, last| value < last)
+}
+
+/// selects the digits forming the minimal number with a non-zero first digit
+///
+/// the first digit is the first occurance of the smallest non-zero digit that
+/// leaves enough digits behind it, the others are the minimal selection after it.
+/// panics if there is no such digit
+fn find_minimal_selection_without_leading_zero(line: &str, digits: usize) -> Vec<Extremal> {
+    let length = line.len();
+    if digits == 0 {
+        return Vec::new();
+    }
+    if digits > length {
+        panic!("requested {} digits from a line of {}", digits, length);
+    }
+    let mut first: Option<Extremal> = None;
+    for (position, symbol) in line.chars().take(length - digits + 1).enumerate() {
+        let value = to_numerical_value(symbol);
+        if value != 0 && first.is_none_or(|first| value < first.value) {
+            first = Some(Extremal { value, position });
+        }
+    }
+    let first = match first {
+        Some(first) => first,
+        None => panic!("no non-zero digit to start {} digits in >{}<", digits, line),
+    };
+
+    let mut selected = vec![first];
+    selected.extend(select_by_stack(
+        &line[first.position + 1..],
+        digits - 1,
+        first.position + 1,
+        |value, last| value < last,
+    ));
+    selected
+}
+
+/// selects the digits forming the maximal power, where selected positions
+/// are at least `gap` apart (a gap of 1 allows neighbouring digits)
+///
+/// every digit is taken greedily as the first largest digit in the window
+/// that leaves room for the remaining ones. Both window ends only move right,
+/// so a deque of candidates with decreasing values finds each maximum, O(n).
+///
+/// panics if the digits do not fit into the line with the gap
+fn find_maximal_selection_with_gap(line: &str, digits: usize, gap: usize) -> Vec<Extremal> {
+    if gap == 0 {
+        panic!("gap needs to be at least 1");
+    }
+    if digits == 0 {
+        return Vec::new();
+    }
+    let values: Vec<i64> = line.chars().map(to_numerical_value).collect();
+    let length = values.len();
+    if (digits - 1) * gap >= length {
+        panic!(
+            "requested {} digits {} apart from a line of {}",
+            digits, gap, length
+        );
+    }
+
+    let mut selected: Vec<Extremal> = Vec::with_capacity(digits);
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+    let mut next_candidate = 0;
+    let mut first_allowed = 0;
+    for selected_count in 0..digits {
+        let last_allowed = length - 1 - (digits - 1 - selected_count) * gap;
+        while next_candidate <= last_allowed {
+            while candidates
+                .back()
+                .is_some_and(|&back| values[back] < values[next_candidate])
+            {
+                candidates.pop_back();
+            }
+            candidates.push_back(next_candidate);
+            next_candidate += 1;
+        }
+        while candidates
+            .front()
+            .is_some_and(|&front| front < first_allowed)
+        {
+            candidates.pop_front();
+        }
+        let position = match candidates.front() {
+            Some(&position) => position,
+            None => panic!("no candidate left in window"),
+        };
+        selected.push(Extremal {
+            value: values[position],
+            position,
+        });
+        first_allowed = position + gap;
+    }
+
+    selected
+}
+
+/// rule how digits are selected from a line
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Selection {
+    /// largest number (the puzzle)
+    Maximal,
+    /// smallest number, optionally forbidding a leading zero
+    Minimal { leading_zero: bool },
+    /// largest number with selected positions at least `gap` apart
+    MaximalWithGap { gap: usize },
+}
+
+impl Selection {
+    /// selects `digits` digits from the line following this rule
+    pub fn select(&self, line: &str, digits: usize) -> Vec<Extremal> {
+        match *self {
+            Selection::Maximal => find_maximal_selection(line, digits),
+            Selection::Minimal { leading_zero: true } => find_minimal_selection(line, digits),
+            Selection::Minimal {
+                leading_zero: false,
+            } => find_minimal_selection_without_leading_zero(line, digits),
+            Selection::MaximalWithGap { gap } => find_maximal_selection_with_gap(line, digits, gap),
+        }
+    }
+}
+
+/// writes the selected digits as number without leading zeros
+fn selection_to_power(selection: &[Extremal]) -> String {
+    let power: String = selection
+        .iter()
+        .skip_while(|digit| digit.value == 0)
+        .filter_map(|digit| char::from_digit(digit.value as u32, 36))
+        .collect();
+    if power.is_empty() {
+        String::from("0")
+    } else {
+        power
+    }
+}
+
+/// finds the power of the selected digits, with `Selection::Maximal` following
+/// day 3 of Advent of Code 2025, as digits of the line so any number of digits fits
+///
+/// panics if requested digits exceed the ones available
+fn find_power(line: &str, digits: usize, selection: Selection) -> String {
+    selection_to_power(&selection.select(line, digits))
+}
+
+/// sum of numbers of any length in a base
+struct PowerSum {
+    base: u32,
+    /// digits in the base, least significant first
+    digits: Vec<u32>,
+}
+
+impl PowerSum {
+    pub fn new(base: u32) -> Self {
+        PowerSum {
+            base,
+            digits: vec![0],
+        }
+    }
+
+    /// adds a number given by its digits in the base
+    pub fn add(&mut self, number: &str) {
+        let mut carry = 0;
+        for (index, symbol) in number.chars().rev().enumerate() {
+            if index == self.digits.len() {
+                self.digits.push(0);
+            }
+            let digit = self.digits[index] + to_numerical_value(symbol) as u32 + carry;
+            self.digits[index] = digit % self.base;
+            carry = digit / self.base;
+        }
+        let mut index = number.len();
+        while carry > 0 {
+            if index == self.digits.len() {
+                self.digits.push(0);
+            }
+            let digit = self.digits[index] + carry;
+            self.digits[index] = digit % self.base;
+            carry = digit / self.base;
+            index += 1;
+        }
+    }
+}
+
+impl fmt::Display for PowerSum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let significant = self.digits.iter().rposition(|&digit| digit != 0);
+        match significant {
+            None => write!(f, "0"),
+            Some(last) => {
+                for &digit in self.digits[..=last].iter().rev() {
+                    if let Some(symbol) = char::from_digit(digit, self.base) {
+                        write!(f, "{}", symbol)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+const HIGHLIGHT_START: &str = "\x1b[1;32m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+/// returns the line with the selected digits highlighted by terminal colors
+fn highlight_selection(line: &str, selection: &[Extremal]) -> String {
+    let mut selected = selection.iter().map(|digit| digit.position).peekable();
+    let mut highlighted = String::new();
+    for (position, symbol) in line.chars().enumerate() {
+        if selected.next_if_eq(&position).is_some() {
+            highlighted.push_str(HIGHLIGHT_START);
+            highlighted.push(symbol);
+            highlighted.push_str(HIGHLIGHT_END);
+        } else {
+            highlighted.push(symbol);
+        }
+    }
+    highlighted
+}
+
+enum Mode {
+    /// print the summed up power of both parts
+    Sums,
+    /// print every line with the selected digits highlighted
+    Highlight { digits: usize },
+}
+
+/// removes `--select=<max|min|min-nonzero|gap=N>` from the arguments and returns
+/// how digits are selected, by default the maximum
+fn parse_selection(args: &mut Vec<String>) -> Selection {
+    let mut selection = Selection::Maximal;
+    args.retain(|arg| match arg.strip_prefix("--select=") {
+        Some(value) => {
+            selection = match value {
+                "max" => Selection::Maximal,
+                "min" => Selection::Minimal { leading_zero: true },
+                "min-nonzero" => Selection::Minimal {
+                    leading_zero: false,
+                },
+                _ => match value.strip_prefix("gap=").map(str::parse::<usize>) {
+                    Some(Ok(gap)) if gap > 0 => Selection::MaximalWithGap { gap },
+                    _ => panic!(
+                        "unknown selection >{}<, expected max, min, min-nonzero or gap=N",
+                        value
+                    ),
+                },
+            };
+            false
+        }
+        None => true,
+    });
+    selection
+}
+
+/// removes `--base=<base>` from the arguments and returns
+/// the base lines are written in, by default decimal
+fn parse_base(args: &mut Vec<String>) -> u32 {
+    let mut base = DECIMAL;
+    args.retain(|arg| match arg.strip_prefix("--base=") {
+        Some(value) => {
+            base = match value.parse::<u32>() {
+                Ok(value) if (2..=36).contains(&value) => value,
+                _ => panic!("unsupported base >{}<, expected 2 to 36", value),
+            };
+            false
+        }
+        None => true,
+    });
+    base
+}
+
+fn parse_mode(args: &[String]) -> Mode {
+    match args.first().map(String::as_str) {
+        None => Mode::Sums,
+        Some("--highlight") => Mode::Highlight {
+            digits: match args.get(1) {
+                None => 12,
+                Some(arg) => match arg.parse::<usize>() {
+                    Ok(digits) => digits,
+                    Err(err) => panic!("could not parse digits >{}<: {}", arg, err),
+                },
+            },
+        },
+        Some(arg) => panic!("unknown argument >{}<, expected --highlight", arg),
+    }
+}
+
 fn main() {
     let start = std::time::Instant::now();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let selection = parse_selection(&mut args);
+    let base = parse_base(&mut args);
+    let mode = parse_mode(&args);
 
     let code = match std::fs::read_to_string("input.txt") {
         Ok(file) => file,
         Err(err) => panic!("Could not read file: {}", err),
     };
+    let lines = match read_lines(&code, base) {
+        Ok(lines) => lines,
+        Err(err) => panic!("Could not read digits: {}", err),
+    };
+
+    if let Mode::Highlight { digits } = mode {
+        for line in &lines {
+            let selected = selection.select(line, digits);
+            println!(
+                "{} -> {}",
+                highlight_selection(line, &selected),
+                selection_to_power(&selected)
+            );
+        }
+        return;
+    }
 
-    let mut maximal_power_pt1 = 0;
-    let mut maximal_power_pt2 = 0;
+    let mut maximal_power_pt1 = PowerSum::new(base);
+    let mut maximal_power_pt2 = PowerSum::new(base);
 
-    for line in code.lines() {
-        let power_pt1 = find_maximal_power(line, 2);
-        maximal_power_pt1 += power_pt1;
+    for line in lines {
+        let power_pt1 = find_power(line, 2, selection);
+        maximal_power_pt1.add(&power_pt1);
 
-        let power_pt2 = find_maximal_power(line, 12);
-        maximal_power_pt2 += power_pt2;
+        let power_pt2 = find_power(line, 12, selection);
+        maximal_power_pt2.add(&power_pt2);
     }
 
     println!("Part 1 - Summed up power is {}", maximal_power_pt1);
@@ -88,27 +485,312 @@ fn main() {
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
-    use crate::find_maximal_power;
+    use crate::{
+        DECIMAL, Extremal, HIGHLIGHT_END, HIGHLIGHT_START, PowerSum, ReadError, Selection,
+        find_maximal_power_by_windows, find_maximal_selection, find_power, highlight_selection,
+        read_lines,
+    };
+
+    /// small xorshift generator, good enough to produce test lines
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
 
     #[test]
     fn find_maximal_power__with_multiple_maxima__finds_power() {
         let input = "000900800900";
-        assert_eq!(99, find_maximal_power(input, 2));
-        assert_eq!(98900, find_maximal_power(input, 5));
+        assert_eq!("99", find_power(input, 2, Selection::Maximal));
+        assert_eq!("98900", find_power(input, 5, Selection::Maximal));
     }
 
     #[test]
     fn find_maximal_power__with_maximum_at_end__finds_power() {
         let input = "0007008009";
-        assert_eq!(89, find_maximal_power(input, 2));
-        assert_eq!(809, find_maximal_power(input, 3));
-        assert_eq!(78009, find_maximal_power(input, 5));
+        assert_eq!("89", find_power(input, 2, Selection::Maximal));
+        assert_eq!("809", find_power(input, 3, Selection::Maximal));
+        assert_eq!("78009", find_power(input, 5, Selection::Maximal));
     }
 
     #[test]
     #[should_panic]
     fn find_maximal_power__given_more_digits_than_available__panics() {
         let input = "321";
-        find_maximal_power(input, 4);
+        find_power(input, 4, Selection::Maximal);
+    }
+
+    #[test]
+    fn find_maximal_power__for_random_lines__matches_window_search() {
+        let mut state = 0x2025_0103;
+        for _ in 0..2000 {
+            let length = 1 + (next_random(&mut state) % 40) as usize;
+            let line: String = (0..length)
+                .map(|_| char::from(b'0' + (next_random(&mut state) % 10) as u8))
+                .collect();
+            let digits = 1 + (next_random(&mut state) as usize) % length.min(18);
+
+            assert_eq!(
+                find_maximal_power_by_windows(&line, digits).to_string(),
+                find_power(&line, digits, Selection::Maximal),
+                "line {} with {} digits",
+                line,
+                digits
+            );
+        }
+    }
+
+    #[test]
+    fn find_maximal_selection__with_multiple_maxima__selects_first_positions() {
+        let input = "000900800900";
+        assert_eq!(
+            vec![
+                Extremal {
+                    value: 9,
+                    position: 3
+                },
+                Extremal {
+                    value: 9,
+                    position: 9
+                },
+            ],
+            find_maximal_selection(input, 2)
+        );
+        let positions: Vec<usize> = find_maximal_selection(input, 5)
+            .iter()
+            .map(|digit| digit.position)
+            .collect();
+        assert_eq!(vec![3, 6, 9, 10, 11], positions);
+    }
+
+    #[test]
+    fn highlight_selection__for_selected_digits__marks_them() {
+        let input = "0007008009";
+        let selection = find_maximal_selection(input, 2);
+        assert_eq!(
+            format!(
+                "000700{}8{}00{}9{}",
+                HIGHLIGHT_START, HIGHLIGHT_END, HIGHLIGHT_START, HIGHLIGHT_END
+            ),
+            highlight_selection(input, &selection)
+        );
+    }
+
+    #[test]
+    fn find_maximal_power__with_30_digits__does_not_overflow() {
+        let input = "1".repeat(10) + &"9".repeat(25) + "0123456789";
+        assert_eq!(
+            "9".repeat(25) + "56789",
+            find_power(&input, 30, Selection::Maximal)
+        );
+    }
+
+    #[test]
+    fn find_maximal_power__with_100_digits__keeps_whole_line() {
+        let input = "0123456789".repeat(10);
+        assert_eq!(&input[1..], find_power(&input, 100, Selection::Maximal));
+        assert_eq!("9".repeat(10), find_power(&input, 10, Selection::Maximal));
+    }
+
+    #[test]
+    fn find_maximal_power__selecting_only_zeros__is_zero() {
+        assert_eq!("0", find_power("000", 2, Selection::Maximal));
+    }
+
+    #[test]
+    fn PowerSum_add__beyond_i64__carries_into_new_digits() {
+        let mut sum = PowerSum::new(DECIMAL);
+        assert_eq!("0", sum.to_string());
+        sum.add(&"9".repeat(30));
+        sum.add("1");
+        assert_eq!(format!("1{}", "0".repeat(30)), sum.to_string());
+        sum.add(&"9".repeat(100));
+        assert_eq!(
+            format!("1{}{}", "0".repeat(70), "9".repeat(30)),
+            sum.to_string()
+        );
+    }
+
+    /// brute force over all subsequences, returns the value of the best one
+    /// as number, None if there is no valid selection
+    fn best_subsequence(line: &str, digits: usize, selection: Selection) -> Option<u64> {
+        let values: Vec<u64> = line.bytes().map(|byte| (byte - b'0') as u64).collect();
+        let mut best: Option<u64> = None;
+        for mask in 0u32..(1 << values.len()) {
+            if mask.count_ones() as usize != digits {
+                continue;
+            }
+            let positions: Vec<usize> =
+                (0..values.len()).filter(|i| mask & (1 << i) != 0).collect();
+            let number = positions
+                .iter()
+                .fold(0, |number, &i| number * 10 + values[i]);
+            let valid = match selection {
+                Selection::Maximal => true,
+                Selection::Minimal { leading_zero } => leading_zero || values[positions[0]] != 0,
+                Selection::MaximalWithGap { gap } => {
+                    positions.windows(2).all(|pair| pair[1] - pair[0] >= gap)
+                }
+            };
+            if !valid {
+                continue;
+            }
+            best = match (best, selection) {
+                (None, _) => Some(number),
+                (Some(best), Selection::Minimal { .. }) => Some(best.min(number)),
+                (Some(best), _) => Some(best.max(number)),
+            };
+        }
+        best
+    }
+
+    #[test]
+    fn Selection_select__minimal__allows_leading_zero() {
+        let input = "3102";
+        assert_eq!(
+            "2",
+            find_power(input, 2, Selection::Minimal { leading_zero: true })
+        );
+        assert_eq!(
+            "102",
+            find_power(input, 3, Selection::Minimal { leading_zero: true })
+        );
+    }
+
+    #[test]
+    fn Selection_select__minimal_without_leading_zero__starts_with_non_zero() {
+        let selection = Selection::Minimal {
+            leading_zero: false,
+        };
+        let positions: Vec<usize> = selection
+            .select("3102", 2)
+            .iter()
+            .map(|digit| digit.position)
+            .collect();
+        assert_eq!(vec![1, 2], positions);
+        assert_eq!("10", find_power("3102", 2, selection));
+        assert_eq!("202", find_power("0902052", 3, selection));
+    }
+
+    #[test]
+    #[should_panic]
+    fn Selection_select__minimal_without_any_non_zero_lead__panics() {
+        Selection::Minimal {
+            leading_zero: false,
+        }
+        .select("0001", 2);
+    }
+
+    #[test]
+    fn Selection_select__maximal_with_gap__keeps_positions_apart() {
+        let selection = Selection::MaximalWithGap { gap: 2 };
+        assert_eq!("97", find_power("9876", 2, selection));
+        let positions: Vec<usize> = selection
+            .select("1919191", 3)
+            .iter()
+            .map(|digit| digit.position)
+            .collect();
+        assert_eq!(vec![1, 3, 5], positions);
+        assert_eq!("1111", find_power("1919191", 4, selection));
+    }
+
+    #[test]
+    #[should_panic]
+    fn Selection_select__maximal_with_gap_exceeding_line__panics() {
+        Selection::MaximalWithGap { gap: 3 }.select("12345", 3);
+    }
+
+    #[test]
+    fn Selection_select__for_random_lines__matches_brute_force() {
+        let mut state = 0x2025_0317;
+        for _ in 0..500 {
+            let length = 1 + (next_random(&mut state) % 12) as usize;
+            let line: String = (0..length)
+                .map(|_| char::from(b'0' + (next_random(&mut state) % 4) as u8))
+                .collect();
+            let digits = 1 + (next_random(&mut state) as usize) % length;
+            let gap = 1 + (next_random(&mut state) as usize) % 3;
+
+            for selection in [
+                Selection::Maximal,
+                Selection::Minimal { leading_zero: true },
+                Selection::Minimal {
+                    leading_zero: false,
+                },
+                Selection::MaximalWithGap { gap },
+            ] {
+                let Some(expected) = best_subsequence(&line, digits, selection) else {
+                    continue;
+                };
+                assert_eq!(
+                    expected.to_string(),
+                    find_power(&line, digits, selection),
+                    "line {} with {} digits, {:?}",
+                    line,
+                    digits,
+                    selection
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_lines__with_crlf_and_blank_lines__returns_digit_lines() {
+        assert_eq!(
+            Ok(vec!["123", "456"]),
+            read_lines("123\r\n\r\n456\r\n", DECIMAL)
+        );
+    }
+
+    #[test]
+    fn read_lines__with_stray_characters__reports_line_and_column() {
+        assert_eq!(
+            Err(ReadError {
+                line: 2,
+                column: 3,
+                symbol: ' ',
+                base: DECIMAL
+            }),
+            read_lines("123\n45 6\n", DECIMAL)
+        );
+        assert_eq!(
+            Err(ReadError {
+                line: 1,
+                column: 4,
+                symbol: '\r',
+                base: DECIMAL
+            }),
+            read_lines("123\r456", DECIMAL)
+        );
+        assert_eq!(
+            "unexpected character 'a' for base 10 at line 1, column 2",
+            read_lines("1a", DECIMAL).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn find_power__with_hex_digits__compares_numeric_values() {
+        let lines = read_lines("19fa0b\n", 16).unwrap();
+        assert_eq!("fb", find_power(lines[0], 2, Selection::Maximal));
+        assert_eq!("fa0b", find_power(lines[0], 4, Selection::Maximal));
+        assert_eq!(
+            "10",
+            find_power(
+                lines[0],
+                2,
+                Selection::Minimal {
+                    leading_zero: false
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn PowerSum_add__in_hex__carries_at_sixteen() {
+        let mut sum = PowerSum::new(16);
+        sum.add("ff");
+        sum.add("1");
+        assert_eq!("100", sum.to_string());
     }
 }
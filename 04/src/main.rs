@@ -1,116 +1,1675 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// a paper roll can be taken, if it has less neighbours than this
+const NEIGHBOUR_LIMIT: i32 = 4;
+
+/// shape of the cells around a paper roll counted as neighbours
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Neighbourhood {
+    /// all cells within the radius in both directions (a square)
+    Moore,
+    /// all cells within the radius in manhattan distance (a diamond)
+    VonNeumann,
+}
+
+/// rule when a paper roll can be taken from storage
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct AccessRule {
+    neighbourhood: Neighbourhood,
+    radius: i32,
+    /// a paper roll can be taken, if it has less neighbours than this
+    threshold: i32,
+    /// whether the storage wraps around at its borders (a torus)
+    wrap: bool,
+}
+
+impl AccessRule {
+    /// the rule of the puzzle: less than 4 of the 8 cells around
+    pub fn new() -> Self {
+        AccessRule {
+            neighbourhood: Neighbourhood::Moore,
+            radius: 1,
+            threshold: NEIGHBOUR_LIMIT,
+            wrap: false,
+        }
+    }
+
+    /// offsets of all neighbours relative to a cell
+    fn offsets(&self) -> Vec<(i32, i32)> {
+        let mut offsets = Vec::new();
+        for r in -self.radius..=self.radius {
+            for c in -self.radius..=self.radius {
+                let inside = match self.neighbourhood {
+                    Neighbourhood::Moore => true,
+                    Neighbourhood::VonNeumann => r.abs() + c.abs() <= self.radius,
+                };
+                if inside && (r, c) != (0, 0) {
+                    offsets.push((r, c));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+/// when paper rolls taken within a round unblock their neighbours
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum RemovalMode {
+    /// rolls are taken while scanning, later cells of the same round
+    /// already see them gone, so the result depends on the scan order
+    Sequential,
+    /// all removable rolls are determined on a snapshot of the round
+    /// before taking any of them
+    Synchronous,
+}
+
+/// what happened to a cell of the storage while taking paper rolls
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum CellHistory {
+    /// never held a paper roll
+    Empty,
+    /// its paper roll was taken in the round (counted from 1)
+    TakenInRound(usize),
+    /// its paper roll could not be taken
+    Remaining,
+}
+
+/// colours of the exported images (red, green, blue)
+const EMPTY_COLOUR: [u8; 3] = [24, 24, 32];
+const ROLL_COLOUR: [u8; 3] = [230, 230, 220];
+const TAKEN_COLOUR: [u8; 3] = [220, 40, 40];
+const TAKEN_BEFORE_COLOUR: [u8; 3] = [60, 70, 130];
+
+/// edge length of a cell in the exported images in pixels
+const PIXELS_PER_CELL: usize = 4;
+
+/// most cells drawn per frame, a PPM frame takes `3 * PIXELS_PER_CELL²`
+/// bytes per cell, so this keeps a frame below 500 MB
+const MAX_FRAME_CELLS: u64 = 10_000_000;
+
+/// error of `export`, the frames would be too large to draw
+#[derive(Debug, PartialEq)]
+struct ExportError {
+    cells: u64,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "frames of {} cells are too large, at most {} cells can be drawn",
+            self.cells, MAX_FRAME_CELLS
+        )
+    }
+}
+
+/// paper rolls taken per round, rounds without removals are left out,
+/// along with the round every paper roll was taken in
+#[derive(Debug, PartialEq)]
+struct RemovalReport {
+    taken_per_round: Vec<usize>,
+    /// rows and columns drawn in the frames
+    extent: (RangeInclusive<i32>, RangeInclusive<i32>),
+    /// the round every paper roll was taken in, `None` if it remained
+    rolls: HashMap<(i32, i32), Option<usize>>,
+}
+
+impl RemovalReport {
+    pub fn rounds(&self) -> usize {
+        self.taken_per_round.len()
+    }
+
+    pub fn taken(&self) -> usize {
+        self.taken_per_round.iter().sum()
+    }
+
+    /// cells drawn per frame
+    pub fn cells(&self) -> u64 {
+        let (rows, cols) = &self.extent;
+        let span = |range: &RangeInclusive<i32>| {
+            (*range.end() as i64 - *range.start() as i64 + 1).max(0) as u64
+        };
+        span(rows).saturating_mul(span(cols))
+    }
+
+    pub fn history(&self, row: i32, col: i32) -> CellHistory {
+        match self.rolls.get(&(row, col)) {
+            None => CellHistory::Empty,
+            Some(None) => CellHistory::Remaining,
+            Some(&Some(round)) => CellHistory::TakenInRound(round),
+        }
+    }
+
+    /// draws the storage after `round` rounds (0 shows the initial storage),
+    /// with `@` for paper rolls, `x` for rolls taken in this round
+    /// and `.` for empty cells
+    pub fn ascii_frame(&self, round: usize) -> String {
+        let (rows, cols) = &self.extent;
+        let mut frame = String::new();
+        for row in rows.clone() {
+            for col in cols.clone() {
+                frame.push(match self.history(row, col) {
+                    CellHistory::Empty => '.',
+                    CellHistory::TakenInRound(taken) if taken == round => 'x',
+                    CellHistory::TakenInRound(taken) if taken < round => '.',
+                    _ => '@',
+                });
+            }
+            frame.push('\n');
+        }
+        frame
+    }
+
+    /// draws all rounds one after the other, each with a header line
+    pub fn ascii_frames(&self) -> String {
+        let mut frames = String::new();
+        for round in 0..=self.rounds() {
+            frames += &format!("== round {} ==\n", round);
+            frames += &self.ascii_frame(round);
+            frames.push('\n');
+        }
+        frames
+    }
+
+    /// draws the storage after `round` rounds as binary PPM image, rolls taken
+    /// in this round in red and rolls taken before in blue
+    pub fn ppm_frame(&self, round: usize) -> Vec<u8> {
+        let (rows, cols) = &self.extent;
+        let width = cols.clone().count();
+        let mut image = format!(
+            "P6\n{} {}\n255\n",
+            width * PIXELS_PER_CELL,
+            rows.clone().count() * PIXELS_PER_CELL
+        )
+        .into_bytes();
+        for row in rows.clone() {
+            let mut line = Vec::with_capacity(width * PIXELS_PER_CELL * 3);
+            for col in cols.clone() {
+                let colour = match self.history(row, col) {
+                    CellHistory::Empty => EMPTY_COLOUR,
+                    CellHistory::TakenInRound(taken) if taken == round => TAKEN_COLOUR,
+                    CellHistory::TakenInRound(taken) if taken < round => TAKEN_BEFORE_COLOUR,
+                    _ => ROLL_COLOUR,
+                };
+                for _ in 0..PIXELS_PER_CELL {
+                    line.extend_from_slice(&colour);
+                }
+            }
+            for _ in 0..PIXELS_PER_CELL {
+                image.extend_from_slice(&line);
+            }
+        }
+        image
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseErrorKind {
+    /// a byte other than `.` and `@`
+    UnexpectedByte { column: usize, byte: u8 },
+    /// a row of another width than the first row
+    RaggedRow { expected: usize, actual: usize },
+    /// a paper roll placed beyond the range of the coordinates
+    OutOfRange { column: usize },
+}
+
+/// error parsing a storage, pointing at the row (1-based) where it was detected
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    row: usize,
+    kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::UnexpectedByte { column, byte } => write!(
+                f,
+                "unexpected byte {:?} at row {}, column {}",
+                byte as char, self.row, column
+            ),
+            ParseErrorKind::RaggedRow { expected, actual } => write!(
+                f,
+                "row {} is {} cells wide, expected {}",
+                self.row, actual, expected
+            ),
+            ParseErrorKind::OutOfRange { column } => write!(
+                f,
+                "paper roll at row {}, column {} is beyond the coordinates",
+                self.row, column
+            ),
+        }
+    }
+}
+
+/// neighbour counts of the paper rolls of a storage
+trait NeighbourCounts {
+    fn get(&self, row: i32, col: i32) -> i32;
+
+    /// counts a taken neighbour of the paper roll, returns its new count
+    fn decrement(&mut self, row: i32, col: i32) -> i32;
+}
+
+impl NeighbourCounts for HashMap<(i32, i32), i32> {
+    fn get(&self, row: i32, col: i32) -> i32 {
+        self[&(row, col)]
+    }
+
+    fn decrement(&mut self, row: i32, col: i32) -> i32 {
+        let count = self
+            .get_mut(&(row, col))
+            .expect("only paper rolls are counted");
+        *count -= 1;
+        *count
+    }
+}
+
+/// a storage of paper rolls, taking them follows its access rule
+trait RollStorage {
+    type Counts: NeighbourCounts;
+
+    fn take_paper_roll(&mut self, row: i32, col: i32);
+
+    fn is_paper_roll(&self, row: i32, col: i32) -> bool;
+
+    fn count_neighbours(&self, row: i32, col: i32) -> i32;
+
+    /// positions of all cells around a cell, which are within the storage
+    fn neighbours(&self, row: i32, col: i32) -> Vec<(i32, i32)>;
+
+    /// positions of all paper rolls, row by row
+    fn paper_rolls(&self) -> Vec<(i32, i32)>;
+
+    /// a paper roll can be taken, if it has less neighbours than this
+    fn threshold(&self) -> i32;
+
+    /// rows and columns covering all paper rolls
+    fn extent(&self) -> (RangeInclusive<i32>, RangeInclusive<i32>);
+
+    /// counts the neighbours of all paper rolls at once
+    fn count_all_neighbours(&self) -> Self::Counts;
+
+    fn is_accessible(&self, row: i32, col: i32) -> bool {
+        self.count_neighbours(row, col) < self.threshold()
+    }
+
+    /// counts paper rolls that can be taken in the current situation
+    fn count_accessible(&self) -> usize {
+        let neighbours = self.count_all_neighbours();
+        self.paper_rolls()
+            .into_iter()
+            .filter(|&(row, col)| neighbours.get(row, col) < self.threshold())
+            .count()
+    }
+
+    /// takes paper rolls until none can be taken. Returns the taken paper rolls
+    ///
+    /// neighbours are counted once per cell; taking a paper roll decrements the
+    /// counts around it and queues the neighbours dropping below the threshold,
+    /// so only affected cells are examined again
+    fn take_all_by_worklist(&mut self) -> usize {
+        let mut neighbours = self.count_all_neighbours();
+        let mut worklist: VecDeque<(i32, i32)> = self
+            .paper_rolls()
+            .into_iter()
+            .filter(|&(row, col)| neighbours.get(row, col) < self.threshold())
+            .collect();
+
+        let mut taken = 0;
+        while let Some((row, col)) = worklist.pop_front() {
+            self.take_paper_roll(row, col);
+            taken += 1;
+            for (r, c) in self.neighbours(row, col) {
+                if !self.is_paper_roll(r, c) {
+                    continue;
+                }
+                // queued exactly once, when dropping below the threshold
+                if neighbours.decrement(r, c) == self.threshold() - 1 {
+                    worklist.push_back((r, c));
+                }
+            }
+        }
+        taken
+    }
+
+    /// takes paper rolls in rounds until none can be taken, rescanning all
+    /// paper rolls and recounting their neighbours every round
+    fn take_all_by_rounds(&mut self, mode: RemovalMode) -> RemovalReport {
+        let extent = self.extent();
+        let mut rolls: HashMap<(i32, i32), Option<usize>> = self
+            .paper_rolls()
+            .into_iter()
+            .map(|position| (position, None))
+            .collect();
+        let mut taken_per_round = Vec::new();
+        loop {
+            let round = taken_per_round.len() + 1;
+            let taken = match mode {
+                RemovalMode::Sequential => {
+                    let mut taken = Vec::new();
+                    for (row, col) in self.paper_rolls() {
+                        if self.is_accessible(row, col) {
+                            self.take_paper_roll(row, col);
+                            taken.push((row, col));
+                        }
+                    }
+                    taken
+                }
+                RemovalMode::Synchronous => {
+                    let neighbours = self.count_all_neighbours();
+                    let taken: Vec<(i32, i32)> = self
+                        .paper_rolls()
+                        .into_iter()
+                        .filter(|&(row, col)| neighbours.get(row, col) < self.threshold())
+                        .collect();
+                    for &(row, col) in &taken {
+                        self.take_paper_roll(row, col);
+                    }
+                    taken
+                }
+            };
+
+            if taken.is_empty() {
+                break;
+            }
+            for position in &taken {
+                rolls.insert(*position, Some(round));
+            }
+            taken_per_round.push(taken.len());
+        }
+        RemovalReport {
+            taken_per_round,
+            extent,
+            rolls,
+        }
+    }
+}
+
+/// neighbour counts of all cells of a dense storage, row by row
+struct DenseCounts {
+    counts: Vec<i32>,
+    cols: usize,
+}
+
+impl NeighbourCounts for DenseCounts {
+    fn get(&self, row: i32, col: i32) -> i32 {
+        self.counts[row as usize * self.cols + col as usize]
+    }
+
+    fn decrement(&mut self, row: i32, col: i32) -> i32 {
+        let count = &mut self.counts[row as usize * self.cols + col as usize];
+        *count -= 1;
+        *count
+    }
+}
+
+/// bits in a word of a packed storage row
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// paper rolls packed as bits, a row is `words_per_row` words with column `col`
+/// at bit `col % WORD_BITS` of word `col / WORD_BITS`; bits past the last
+/// column are kept unset
 struct PaperRollStorage {
-    storage: Vec<Vec<bool>>,
+    storage: Vec<u64>,
+    words_per_row: usize,
     rows: i32,
     cols: i32,
+    rule: AccessRule,
+    /// offsets of the neighbours following the rule, when wrapping reduced
+    /// to the storage size so no cell is counted twice
+    offsets: Vec<(i32, i32)>,
 }
 
-impl PaperRollStorage {
-    pub fn new(input: String) -> Self {
-        let lines = input.lines();
-        let mut rows = 0;
-        let mut cols = 0;
-        let mut storage = Vec::<Vec<bool>>::new();
+impl TryFrom<&str> for PaperRollStorage {
+    type Error = ParseError;
 
-        for (index, line) in lines.enumerate() {
-            if cols == 0 {
-                cols = line.len();
-            }
+    /// reads rows of `.` (empty) and `@` (paper roll) of the same width,
+    /// line ends may be CRLF and trailing blank lines are ignored
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let mut lines: Vec<&str> = input.lines().collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let cols = lines.first().map_or(0, |line| line.len());
+        let words_per_row = cols.div_ceil(WORD_BITS);
+        let mut storage = Vec::<u64>::with_capacity(lines.len() * words_per_row);
+
+        for (index, line) in lines.iter().enumerate() {
             if cols != line.len() {
-                panic!("non grid shape")
-            }
-            let mut linestorage = Vec::<bool>::new();
-            for entry in line.as_bytes() {
-                linestorage.push(match entry {
-                    b'.' => false,
-                    b'@' => true,
-                    _ => panic!("non storage entry in grid"),
+                return Err(ParseError {
+                    row: index + 1,
+                    kind: ParseErrorKind::RaggedRow {
+                        expected: cols,
+                        actual: line.len(),
+                    },
                 });
             }
-            storage.push(linestorage);
-            rows = index + 1
+            let mut linestorage = vec![0u64; words_per_row];
+            for (col, &entry) in line.as_bytes().iter().enumerate() {
+                match entry {
+                    b'.' => {}
+                    b'@' => linestorage[col / WORD_BITS] |= 1 << (col % WORD_BITS),
+                    byte => {
+                        return Err(ParseError {
+                            row: index + 1,
+                            kind: ParseErrorKind::UnexpectedByte {
+                                column: col + 1,
+                                byte,
+                            },
+                        });
+                    }
+                }
+            }
+            storage.extend(linestorage);
         }
-        PaperRollStorage {
+        let rule = AccessRule::new();
+        Ok(PaperRollStorage {
             storage,
-            rows: rows as i32,
+            words_per_row,
+            rows: lines.len() as i32,
             cols: cols as i32,
+            rule,
+            offsets: rule.offsets(),
+        })
+    }
+}
+
+impl PaperRollStorage {
+    /// panics if the input is no storage, see `try_from` for the errors
+    pub fn new(input: String) -> Self {
+        match Self::try_from(input.as_str()) {
+            Ok(storage) => storage,
+            Err(err) => panic!("Could not read storage: {}", err),
+        }
+    }
+
+    pub fn with_rule(mut self, rule: AccessRule) -> Self {
+        let mut offsets = rule.offsets();
+        if rule.wrap && self.rows > 0 && self.cols > 0 {
+            // on a small torus several offsets reach the same cell
+            for offset in offsets.iter_mut() {
+                *offset = (
+                    offset.0.rem_euclid(self.rows),
+                    offset.1.rem_euclid(self.cols),
+                );
+            }
+            offsets.sort();
+            offsets.dedup();
+            offsets.retain(|&offset| offset != (0, 0));
+        }
+        self.rule = rule;
+        self.offsets = offsets;
+        self
+    }
+
+    /// returns the cell within the storage at the position, wrapping around
+    /// the borders if the rule says so, None if outside the storage
+    fn locate(&self, row: i32, col: i32) -> Option<(i32, i32)> {
+        if self.rule.wrap && self.rows > 0 && self.cols > 0 {
+            return Some((row.rem_euclid(self.rows), col.rem_euclid(self.cols)));
+        }
+        if (row < 0) || (col < 0) || (row >= self.rows) || (col >= self.cols) {
+            return None;
+        }
+        Some((row, col))
+    }
+
+    /// ors into `shifted` the bits of the row moved by `shift` columns,
+    /// so bit `col` is the bit at `col + shift` (unset outside the row)
+    fn or_shifted_row(&self, row: usize, shift: i32, shifted: &mut [u64]) {
+        let words = &self.storage[row * self.words_per_row..(row + 1) * self.words_per_row];
+        let word_shift = shift.div_euclid(WORD_BITS as i32) as isize;
+        let bit_shift = shift.rem_euclid(WORD_BITS as i32) as u32;
+        let word_at = |index: isize| -> u64 {
+            if index < 0 || index as usize >= words.len() {
+                0
+            } else {
+                words[index as usize]
+            }
+        };
+        for (index, word) in shifted.iter_mut().enumerate() {
+            let source = index as isize + word_shift;
+            *word |= word_at(source) >> bit_shift;
+            if bit_shift > 0 {
+                *word |= word_at(source + 1) << (WORD_BITS as u32 - bit_shift);
+            }
         }
     }
 
+    /// counts the neighbours of all cells in a row at once: every neighbour
+    /// offset gives the row of neighbour bits, shifted word-wide, which are
+    /// summed up bit-sliced (`planes[bit]` holds that bit of every count)
+    pub fn count_neighbours_in_row(&self, row: i32) -> Vec<i32> {
+        let plane_count = (usize::BITS - self.offsets.len().leading_zeros()).max(1);
+        let mut planes = vec![vec![0u64; self.words_per_row]; plane_count as usize];
+        let mut neighbours = vec![0u64; self.words_per_row];
+        let last_word_mask = match self.cols as usize % WORD_BITS {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        };
+
+        for (r, c) in &self.offsets {
+            neighbours.fill(0);
+            let Some((r, _)) = self.locate(row + r, 0) else {
+                continue;
+            };
+            self.or_shifted_row(r as usize, *c, &mut neighbours);
+            if self.rule.wrap {
+                // offsets are reduced to 0..cols, the rest comes from the row start
+                self.or_shifted_row(r as usize, *c - self.cols, &mut neighbours);
+            }
+            if let Some(last) = neighbours.last_mut() {
+                *last &= last_word_mask;
+            }
+
+            for (index, &word) in neighbours.iter().enumerate() {
+                let mut carry = word;
+                for plane in planes.iter_mut() {
+                    if carry == 0 {
+                        break;
+                    }
+                    let next_carry = plane[index] & carry;
+                    plane[index] ^= carry;
+                    carry = next_carry;
+                }
+            }
+        }
+
+        (0..self.cols as usize)
+            .map(|col| {
+                planes.iter().enumerate().fold(0, |count, (bit, plane)| {
+                    let set = (plane[col / WORD_BITS] >> (col % WORD_BITS)) & 1;
+                    count | (set as i32) << bit
+                })
+            })
+            .collect()
+    }
+
     pub fn take_paper_roll(&mut self, row: i32, col: i32) {
-        self.storage[row as usize][col as usize] = false;
+        let col = col as usize;
+        self.storage[row as usize * self.words_per_row + col / WORD_BITS] &=
+            !(1 << (col % WORD_BITS));
     }
 
     pub fn is_paper_roll(&self, row: i32, col: i32) -> bool {
-        if (row < 0) || (col < 0) || (row >= self.rows) || (col >= self.cols) {
-            return false;
+        match self.locate(row, col) {
+            Some((row, col)) => {
+                let col = col as usize;
+                let word = self.storage[row as usize * self.words_per_row + col / WORD_BITS];
+                word & (1 << (col % WORD_BITS)) != 0
+            }
+            None => false,
         }
-        self.storage[row as usize][col as usize]
     }
 
     pub fn count_neighbours(&self, row: i32, col: i32) -> i32 {
         let mut count = 0;
-        for r in row - 1..=row + 1 {
-            for c in col - 1..=col + 1 {
-                if r == row && c == col {
-                    continue;
+        for (r, c) in &self.offsets {
+            if self.is_paper_roll(row + r, col + c) {
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl RollStorage for PaperRollStorage {
+    type Counts = DenseCounts;
+
+    fn take_paper_roll(&mut self, row: i32, col: i32) {
+        PaperRollStorage::take_paper_roll(self, row, col)
+    }
+
+    fn is_paper_roll(&self, row: i32, col: i32) -> bool {
+        PaperRollStorage::is_paper_roll(self, row, col)
+    }
+
+    fn count_neighbours(&self, row: i32, col: i32) -> i32 {
+        PaperRollStorage::count_neighbours(self, row, col)
+    }
+
+    fn neighbours(&self, row: i32, col: i32) -> Vec<(i32, i32)> {
+        self.offsets
+            .iter()
+            .filter_map(|(r, c)| self.locate(row + r, col + c))
+            .collect()
+    }
+
+    fn paper_rolls(&self) -> Vec<(i32, i32)> {
+        let mut rolls = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if self.is_paper_roll(row, col) {
+                    rolls.push((row, col));
                 }
-                if self.is_paper_roll(r, c) {
-                    count += 1;
+            }
+        }
+        rolls
+    }
+
+    fn threshold(&self) -> i32 {
+        self.rule.threshold
+    }
+
+    fn extent(&self) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
+        (0..=self.rows - 1, 0..=self.cols - 1)
+    }
+
+    /// counts the neighbours row by row, see `count_neighbours_in_row`
+    fn count_all_neighbours(&self) -> DenseCounts {
+        DenseCounts {
+            counts: (0..self.rows)
+                .flat_map(|row| self.count_neighbours_in_row(row))
+                .collect(),
+            cols: self.cols as usize,
+        }
+    }
+}
+
+/// paper rolls stored by their position only, so the storage may be mostly
+/// empty and reach into negative coordinates, bounded by the range of `i32`
+struct SparsePaperRollStorage {
+    rolls: HashSet<(i32, i32)>,
+    rule: AccessRule,
+    offsets: Vec<(i32, i32)>,
+}
+
+impl FromIterator<(i32, i32)> for SparsePaperRollStorage {
+    fn from_iter<I: IntoIterator<Item = (i32, i32)>>(positions: I) -> Self {
+        let rule = AccessRule::new();
+        SparsePaperRollStorage {
+            rolls: positions.into_iter().collect(),
+            rule,
+            offsets: rule.offsets(),
+        }
+    }
+}
+
+impl TryFrom<&str> for SparsePaperRollStorage {
+    type Error = ParseError;
+
+    /// reads rows of `.` and `@` starting at row and column 0,
+    /// rows may have any width
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        Self::parse(input, (0, 0))
+    }
+}
+
+impl SparsePaperRollStorage {
+    /// reads rows of `.` and `@` like `try_from`, with the first cell
+    /// at `origin` (row, column), which may be negative
+    pub fn parse(input: &str, origin: (i32, i32)) -> Result<Self, ParseError> {
+        let shift = |start: i32, offset: usize| start.checked_add(i32::try_from(offset).ok()?);
+        let mut rolls = Vec::new();
+        for (row, line) in input.lines().enumerate() {
+            for (col, &entry) in line.as_bytes().iter().enumerate() {
+                match entry {
+                    b'.' => {}
+                    b'@' => match shift(origin.0, row).zip(shift(origin.1, col)) {
+                        Some(position) => rolls.push(position),
+                        None => {
+                            return Err(ParseError {
+                                row: row + 1,
+                                kind: ParseErrorKind::OutOfRange { column: col + 1 },
+                            });
+                        }
+                    },
+                    byte => {
+                        return Err(ParseError {
+                            row: row + 1,
+                            kind: ParseErrorKind::UnexpectedByte {
+                                column: col + 1,
+                                byte,
+                            },
+                        });
+                    }
                 }
             }
         }
-        count
+        Ok(rolls.into_iter().collect())
+    }
+
+    /// panics for a wrapping rule, an unbounded storage has no borders to wrap
+    pub fn with_rule(mut self, rule: AccessRule) -> Self {
+        if rule.wrap {
+            panic!("an unbounded storage can not wrap around");
+        }
+        self.rule = rule;
+        self.offsets = rule.offsets();
+        self
+    }
+
+    /// cells at the offsets around a cell, leaving out the ones beyond
+    /// the range of the coordinates
+    fn shifted(&self, row: i32, col: i32) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.offsets
+            .iter()
+            .filter_map(move |&(r, c)| Some((row.checked_add(r)?, col.checked_add(c)?)))
+    }
+}
+
+impl RollStorage for SparsePaperRollStorage {
+    type Counts = HashMap<(i32, i32), i32>;
+
+    fn take_paper_roll(&mut self, row: i32, col: i32) {
+        self.rolls.remove(&(row, col));
+    }
+
+    fn is_paper_roll(&self, row: i32, col: i32) -> bool {
+        self.rolls.contains(&(row, col))
+    }
+
+    fn count_neighbours(&self, row: i32, col: i32) -> i32 {
+        self.shifted(row, col)
+            .filter(|&(r, c)| self.is_paper_roll(r, c))
+            .count() as i32
+    }
+
+    fn neighbours(&self, row: i32, col: i32) -> Vec<(i32, i32)> {
+        self.shifted(row, col).collect()
+    }
+
+    fn paper_rolls(&self) -> Vec<(i32, i32)> {
+        let mut rolls: Vec<(i32, i32)> = self.rolls.iter().copied().collect();
+        rolls.sort();
+        rolls
+    }
+
+    fn threshold(&self) -> i32 {
+        self.rule.threshold
+    }
+
+    /// the bounding box of the paper rolls
+    fn extent(&self) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
+        let rows = self.rolls.iter().map(|&(row, _)| row);
+        let cols = self.rolls.iter().map(|&(_, col)| col);
+        match (
+            rows.clone().min(),
+            rows.max(),
+            cols.clone().min(),
+            cols.max(),
+        ) {
+            (Some(top), Some(bottom), Some(left), Some(right)) => (top..=bottom, left..=right),
+            // no paper rolls, nothing to draw
+            _ => (RangeInclusive::new(0, -1), RangeInclusive::new(0, -1)),
+        }
+    }
+
+    fn count_all_neighbours(&self) -> Self::Counts {
+        self.rolls
+            .iter()
+            .map(|&(row, col)| ((row, col), self.count_neighbours(row, col)))
+            .collect()
+    }
+}
+
+/// small xorshift generator, good enough to generate storages
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// generates a storage of the given size in input format,
+/// with about `percentage` percent of the cells holding a paper roll
+fn generate_storage(rows: usize, cols: usize, percentage: u64, seed: u64) -> String {
+    let mut state = seed;
+    let mut input = String::with_capacity(rows * (cols + 1));
+    for _ in 0..rows {
+        for _ in 0..cols {
+            input.push(if next_random(&mut state) % 100 < percentage {
+                '@'
+            } else {
+                '.'
+            });
+        }
+        input.push('\n');
+    }
+    input
+}
+
+/// removes `--removal=<sequential|synchronous>` from the arguments and returns
+/// the removal mode taken in rounds, by default none (the worklist)
+fn parse_removal_mode(args: &mut Vec<String>) -> Option<RemovalMode> {
+    let mut mode = None;
+    args.retain(|arg| match arg.strip_prefix("--removal=") {
+        Some(value) => {
+            mode = match value {
+                "sequential" => Some(RemovalMode::Sequential),
+                "synchronous" => Some(RemovalMode::Synchronous),
+                _ => panic!(
+                    "unknown removal >{}<, expected sequential or synchronous",
+                    value
+                ),
+            };
+            false
+        }
+        None => true,
+    });
+    mode
+}
+
+fn parse_number(arg: &str) -> i32 {
+    match arg.parse::<i32>() {
+        Ok(number) if number >= 0 => number,
+        _ => panic!("could not parse number >{}<", arg),
     }
 }
 
+/// removes `--neighbourhood=<moore|von-neumann>`, `--radius=N`, `--threshold=N`
+/// and `--wrap` from the arguments and returns the rule, by default the puzzle's
+fn parse_rule(args: &mut Vec<String>) -> AccessRule {
+    let mut rule = AccessRule::new();
+    args.retain(|arg| {
+        if let Some(value) = arg.strip_prefix("--neighbourhood=") {
+            rule.neighbourhood = match value {
+                "moore" => Neighbourhood::Moore,
+                "von-neumann" => Neighbourhood::VonNeumann,
+                _ => panic!(
+                    "unknown neighbourhood >{}<, expected moore or von-neumann",
+                    value
+                ),
+            };
+        } else if let Some(value) = arg.strip_prefix("--radius=") {
+            rule.radius = parse_number(value);
+        } else if let Some(value) = arg.strip_prefix("--threshold=") {
+            rule.threshold = parse_number(value);
+        } else if arg == "--wrap" {
+            rule.wrap = true;
+        } else {
+            return true;
+        }
+        false
+    });
+    rule
+}
+
+/// how the storage keeps its paper rolls
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Backend {
+    /// bit-packed rows of the whole rectangle
+    Dense,
+    /// positions of the paper rolls only
+    Sparse,
+}
+
+/// removes `--storage=<dense|sparse>` from the arguments and returns
+/// the storage backend, by default dense
+fn parse_backend(args: &mut Vec<String>) -> Backend {
+    let mut backend = Backend::Dense;
+    args.retain(|arg| match arg.strip_prefix("--storage=") {
+        Some(value) => {
+            backend = match value {
+                "dense" => Backend::Dense,
+                "sparse" => Backend::Sparse,
+                _ => panic!("unknown storage >{}<, expected dense or sparse", value),
+            };
+            false
+        }
+        None => true,
+    });
+    backend
+}
+
+/// removes `--origin=ROW,COL` from the arguments and returns the position
+/// of the first cell of the input, if given
+fn parse_origin(args: &mut Vec<String>) -> Option<(i32, i32)> {
+    let mut origin = None;
+    args.retain(|arg| match arg.strip_prefix("--origin=") {
+        Some(value) => {
+            let position = value
+                .split_once(',')
+                .and_then(|(row, col)| Some((row.parse().ok()?, col.parse().ok()?)));
+            match position {
+                Some(position) => origin = Some(position),
+                None => panic!("could not parse origin >{}<, expected ROW,COL", value),
+            }
+            false
+        }
+        None => true,
+    });
+    origin
+}
+
+enum Mode {
+    /// print the paper rolls available in both parts
+    Count,
+    /// time taking all paper rolls on a generated square storage
+    Benchmark { size: usize },
+    /// write a PPM image per round and all ASCII frames into the directory
+    Export { directory: String },
+}
+
+fn parse_mode(args: &[String]) -> Mode {
+    match args.first().map(String::as_str) {
+        None => Mode::Count,
+        Some("--benchmark") => Mode::Benchmark {
+            size: match args.get(1) {
+                None => 5000,
+                Some(arg) => match arg.parse::<usize>() {
+                    Ok(size) => size,
+                    Err(err) => panic!("could not parse size >{}<: {}", arg, err),
+                },
+            },
+        },
+        Some("--export") => match args.get(1) {
+            Some(directory) => Mode::Export {
+                directory: directory.clone(),
+            },
+            None => panic!("usage: --export <directory>"),
+        },
+        Some(arg) => panic!(
+            "unknown argument >{}<, expected --benchmark or --export",
+            arg
+        ),
+    }
+}
+
+fn benchmark(size: usize) {
+    let input = generate_storage(size, size, 70, 0x2025_0004);
+
+    let start = std::time::Instant::now();
+    let taken = PaperRollStorage::new(input.clone()).take_all_by_worklist();
+    println!(
+        "worklist: took {} paper rolls in {} ms",
+        taken,
+        start.elapsed().as_nanos() as f64 / 1e6
+    );
+
+    let start = std::time::Instant::now();
+    let taken = PaperRollStorage::new(input)
+        .take_all_by_rounds(RemovalMode::Sequential)
+        .taken();
+    println!(
+        "rescanning: took {} paper rolls in {} ms",
+        taken,
+        start.elapsed().as_nanos() as f64 / 1e6
+    );
+
+    let input = generate_storage(size, size, 2, 0x2025_0025);
+    let start = std::time::Instant::now();
+    let taken = PaperRollStorage::new(input.clone()).take_all_by_worklist();
+    println!(
+        "dense, mostly empty: took {} paper rolls in {} ms",
+        taken,
+        start.elapsed().as_nanos() as f64 / 1e6
+    );
+    let start = std::time::Instant::now();
+    let taken = match SparsePaperRollStorage::try_from(input.as_str()) {
+        Ok(mut storage) => storage.take_all_by_worklist(),
+        Err(err) => panic!("Could not read storage: {}", err),
+    };
+    println!(
+        "sparse, mostly empty: took {} paper rolls in {} ms",
+        taken,
+        start.elapsed().as_nanos() as f64 / 1e6
+    );
+}
+
+/// writes `round_NNN.ppm` per round and `frames.txt` into the directory,
+/// nothing is written if the frames have more than `MAX_FRAME_CELLS` cells
+fn export(report: &RemovalReport, directory: &str) -> Result<(), ExportError> {
+    if report.cells() > MAX_FRAME_CELLS {
+        return Err(ExportError {
+            cells: report.cells(),
+        });
+    }
+    let directory = std::path::Path::new(directory);
+    if let Err(err) = std::fs::create_dir_all(directory) {
+        panic!("Could not create directory: {}", err);
+    }
+    for round in 0..=report.rounds() {
+        let path = directory.join(format!("round_{:03}.ppm", round));
+        if let Err(err) = std::fs::write(path, report.ppm_frame(round)) {
+            panic!("Could not write file: {}", err);
+        }
+    }
+    if let Err(err) = std::fs::write(directory.join("frames.txt"), report.ascii_frames()) {
+        panic!("Could not write file: {}", err);
+    }
+    Ok(())
+}
+
+/// prints both parts for the storage, or exports its rounds
+fn evaluate<S: RollStorage>(mut storage: S, mode: Mode, removal_mode: Option<RemovalMode>) {
+    let available_paper_rolls = storage.count_accessible();
+    println!("[Part 1] paper rolls available: {}", available_paper_rolls);
+
+    if let Mode::Export { directory } = mode {
+        let report = storage.take_all_by_rounds(removal_mode.unwrap_or(RemovalMode::Synchronous));
+        if let Err(err) = export(&report, &directory) {
+            panic!("Could not export: {}", err);
+        }
+        println!(
+            "[Part 2] exported {} rounds taking {} paper rolls to {}",
+            report.rounds(),
+            report.taken(),
+            directory
+        );
+        return;
+    }
+
+    let available_paper_rolls = match removal_mode {
+        None => storage.take_all_by_worklist(),
+        Some(mode) => {
+            let report = storage.take_all_by_rounds(mode);
+            println!(
+                "[Part 2] {:?} removal took {} rounds: {:?}",
+                mode,
+                report.rounds(),
+                report.taken_per_round
+            );
+            report.taken()
+        }
+    };
+    println!("[Part 2] paper rolls available: {}", available_paper_rolls);
+}
+
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let removal_mode = parse_removal_mode(&mut args);
+    let rule = parse_rule(&mut args);
+    let backend = parse_backend(&mut args);
+    let origin = parse_origin(&mut args);
+    if origin.is_some() && backend == Backend::Dense {
+        panic!("--origin needs --storage=sparse");
+    }
+    let mode = parse_mode(&args);
+    if let Mode::Benchmark { size } = mode {
+        benchmark(size);
+        return;
+    }
+
     let start = std::time::Instant::now();
 
     let code = match std::fs::read_to_string("input.txt") {
         Ok(file) => file,
         Err(err) => panic!("Could not read file: {}", err),
     };
-    let mut storage = PaperRollStorage::new(code);
 
-    let mut available_paper_rolls = 0;
-    for row in 0..storage.rows {
-        for col in 0..storage.cols {
-            if !storage.is_paper_roll(row, col) {
-                continue;
+    match backend {
+        Backend::Dense => match PaperRollStorage::try_from(code.as_str()) {
+            Ok(storage) => evaluate(storage.with_rule(rule), mode, removal_mode),
+            Err(err) => panic!("Could not read storage: {}", err),
+        },
+        Backend::Sparse => match SparsePaperRollStorage::parse(&code, origin.unwrap_or((0, 0))) {
+            Ok(storage) => evaluate(storage.with_rule(rule), mode, removal_mode),
+            Err(err) => panic!("Could not read storage: {}", err),
+        },
+    }
+
+    println!(
+        "evaluation took {} ms",
+        start.elapsed().as_nanos() as f64 / 1e6
+    )
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use crate::{
+        AccessRule, CellHistory, ExportError, Neighbourhood, PIXELS_PER_CELL, PaperRollStorage,
+        ParseError, ParseErrorKind, RemovalMode, RollStorage, SparsePaperRollStorage,
+        TAKEN_BEFORE_COLOUR, TAKEN_COLOUR, export, generate_storage,
+    };
+
+    const FULL: &str = "@@@\n@@@\n@@@\n";
+
+    fn rule(neighbourhood: Neighbourhood, radius: i32, threshold: i32, wrap: bool) -> AccessRule {
+        AccessRule {
+            neighbourhood,
+            radius,
+            threshold,
+            wrap,
+        }
+    }
+
+    const EXAMPLE: &str = "..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.
+";
+
+    #[test]
+    fn PaperRollStorage_count_accessible__for_example__is_13() {
+        let storage = PaperRollStorage::new(String::from(EXAMPLE));
+        assert_eq!(13, storage.count_accessible());
+    }
+
+    #[test]
+    fn PaperRollStorage_take_all__for_example__takes_43() {
+        let mut storage = PaperRollStorage::new(String::from(EXAMPLE));
+        assert_eq!(43, storage.take_all_by_worklist());
+        for mode in [RemovalMode::Sequential, RemovalMode::Synchronous] {
+            let mut storage = PaperRollStorage::new(String::from(EXAMPLE));
+            assert_eq!(43, storage.take_all_by_rounds(mode).taken());
+        }
+    }
+
+    #[test]
+    fn PaperRollStorage_take_all_by_rounds__synchronous__differs_from_sequential() {
+        // only the corners have less than 4 neighbours in the beginning
+        let input = "@@@@@@\n@@@@@@\n";
+        let mut storage = PaperRollStorage::new(String::from(input));
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        assert_eq!(vec![4, 4, 4], report.taken_per_round);
+        assert_eq!(3, report.rounds());
+
+        let mut storage = PaperRollStorage::new(String::from(input));
+        let report = storage.take_all_by_rounds(RemovalMode::Sequential);
+        // rolls taken earlier in the scan unblock later ones within the round
+        assert_eq!(vec![5, 6, 1], report.taken_per_round);
+    }
+
+    /// the storage read column by column, so a scan meets the cells in another order
+    fn transpose(input: &str) -> String {
+        let rows: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let mut transposed = String::new();
+        for col in 0..rows[0].len() {
+            for row in &rows {
+                transposed.push(row[col] as char);
             }
+            transposed.push('\n');
+        }
+        transposed
+    }
 
-            if storage.count_neighbours(row, col) < 4 {
-                available_paper_rolls += 1;
+    /// the storage with every row reversed, so a scan runs right to left
+    fn mirror(input: &str) -> String {
+        input
+            .lines()
+            .map(|line| line.chars().rev().collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn PaperRollStorage_take_all_by_rounds__synchronous__does_not_depend_on_scan_order() {
+        let rounds = |input: &str, mode: RemovalMode| {
+            PaperRollStorage::new(String::from(input))
+                .take_all_by_rounds(mode)
+                .taken_per_round
+        };
+        for input in [String::from(EXAMPLE), generate_storage(15, 20, 65, 2020)] {
+            let reordered = [transpose(&input), mirror(&input)];
+
+            let synchronous = rounds(&input, RemovalMode::Synchronous);
+            for other in &reordered {
+                assert_eq!(synchronous, rounds(other, RemovalMode::Synchronous));
             }
+
+            let sequential = rounds(&input, RemovalMode::Sequential);
+            assert!(
+                reordered
+                    .iter()
+                    .any(|other| sequential != rounds(other, RemovalMode::Sequential))
+            );
         }
     }
-    println!("[Part 1] paper rolls available: {}", available_paper_rolls);
 
-    available_paper_rolls = 0;
-    loop {
-        let mut taken_this_time = 0;
-        for row in 0..storage.rows {
-            for col in 0..storage.cols {
-                if !storage.is_paper_roll(row, col) {
-                    continue;
+    #[test]
+    fn PaperRollStorage_take_all_by_rounds__for_example__reports_rounds() {
+        let mut storage = PaperRollStorage::new(String::from(EXAMPLE));
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        assert_eq!(vec![13, 12, 7, 5, 2, 1, 1, 1, 1], report.taken_per_round);
+    }
+
+    #[test]
+    fn PaperRollStorage_take_all_by_worklist__for_generated_storages__matches_rescanning() {
+        for seed in 1..50 {
+            let input = generate_storage(30, 40, 40 + seed % 50, seed);
+            let mut worklist = PaperRollStorage::new(input.clone());
+            let mut rescanning = PaperRollStorage::new(input);
+
+            assert_eq!(
+                rescanning
+                    .take_all_by_rounds(RemovalMode::Sequential)
+                    .taken(),
+                worklist.take_all_by_worklist(),
+                "seed {}",
+                seed
+            );
+            assert_eq!(rescanning.storage, worklist.storage, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn PaperRollStorage_count_neighbours__von_neumann__counts_diamond() {
+        let storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::VonNeumann,
+            1,
+            4,
+            false,
+        ));
+        assert_eq!(4, storage.count_neighbours(1, 1));
+        assert_eq!(2, storage.count_neighbours(0, 0));
+        assert_eq!(3, storage.count_neighbours(0, 1));
+
+        let storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::VonNeumann,
+            2,
+            4,
+            false,
+        ));
+        // (0,1), (0,2), (1,0), (1,1), (2,0)
+        assert_eq!(5, storage.count_neighbours(0, 0));
+    }
+
+    #[test]
+    fn PaperRollStorage_count_neighbours__moore_with_radius__counts_square() {
+        let input = "@@@@@\n@@.@@\n@@@@@\n";
+        let storage = PaperRollStorage::new(String::from(input));
+        assert_eq!(8, storage.count_neighbours(1, 2));
+        assert_eq!(3, storage.count_neighbours(0, 0));
+
+        let storage = PaperRollStorage::new(String::from(input)).with_rule(rule(
+            Neighbourhood::Moore,
+            2,
+            4,
+            false,
+        ));
+        // all 15 cells but the gap itself
+        assert_eq!(14, storage.count_neighbours(1, 2));
+        // 3 rows of 3 columns, without the cell and the gap
+        assert_eq!(7, storage.count_neighbours(0, 0));
+    }
+
+    #[test]
+    fn PaperRollStorage_count_neighbours__with_wrap__counts_across_borders_once() {
+        let storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::Moore,
+            1,
+            4,
+            true,
+        ));
+        assert_eq!(8, storage.count_neighbours(0, 0));
+        assert!(storage.is_paper_roll(-1, 3));
+
+        let storage = PaperRollStorage::new(String::from("@@\n@@\n")).with_rule(rule(
+            Neighbourhood::Moore,
+            1,
+            4,
+            true,
+        ));
+        assert_eq!(3, storage.count_neighbours(0, 0));
+
+        let storage = PaperRollStorage::new(String::from("@.\n..\n")).with_rule(rule(
+            Neighbourhood::Moore,
+            5,
+            4,
+            true,
+        ));
+        assert_eq!(0, storage.count_neighbours(0, 0));
+    }
+
+    #[test]
+    fn PaperRollStorage_take_all__with_threshold__takes_accordingly() {
+        let mut storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::Moore,
+            1,
+            3,
+            false,
+        ));
+        assert_eq!(0, storage.count_accessible());
+        assert_eq!(0, storage.take_all_by_worklist());
+
+        // corners have 3 neighbours, afterwards the edges 2, then the centre 0
+        let mut storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::Moore,
+            1,
+            4,
+            false,
+        ));
+        assert_eq!(
+            vec![4, 4, 1],
+            storage
+                .take_all_by_rounds(RemovalMode::Synchronous)
+                .taken_per_round
+        );
+
+        // on the torus every cell has all 8 others as neighbours
+        let mut storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::Moore,
+            1,
+            8,
+            true,
+        ));
+        assert_eq!(0, storage.take_all_by_worklist());
+        let mut storage = PaperRollStorage::new(String::from(FULL)).with_rule(rule(
+            Neighbourhood::Moore,
+            1,
+            9,
+            true,
+        ));
+        assert_eq!(9, storage.take_all_by_worklist());
+    }
+
+    #[test]
+    fn PaperRollStorage_take_all_by_worklist__with_rules__matches_rescanning() {
+        let mut seed = 0;
+        for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+            for radius in 1..=3 {
+                for wrap in [false, true] {
+                    seed += 1;
+                    let threshold = 2 + (seed as i32 * 7) % (2 * radius * (radius + 1));
+                    let rule = rule(neighbourhood, radius, threshold, wrap);
+                    let input = generate_storage(12, 17, 60, seed);
+                    let mut worklist = PaperRollStorage::new(input.clone()).with_rule(rule);
+                    let mut rescanning = PaperRollStorage::new(input).with_rule(rule);
+
+                    assert_eq!(
+                        rescanning
+                            .take_all_by_rounds(RemovalMode::Sequential)
+                            .taken(),
+                        worklist.take_all_by_worklist(),
+                        "{:?}",
+                        rule
+                    );
+                    assert_eq!(rescanning.storage, worklist.storage, "{:?}", rule);
                 }
+            }
+        }
+    }
 
-                if storage.count_neighbours(row, col) < 4 {
-                    storage.take_paper_roll(row, col);
-                    taken_this_time += 1;
+    #[test]
+    fn RemovalReport_history__synchronous__records_round_per_roll() {
+        let mut storage = PaperRollStorage::new(String::from(".@@@\n.@@@\n.@@@\n"));
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        assert_eq!(CellHistory::Empty, report.history(0, 0));
+        assert_eq!(CellHistory::TakenInRound(1), report.history(0, 1));
+        assert_eq!(CellHistory::TakenInRound(2), report.history(1, 1));
+        assert_eq!(CellHistory::TakenInRound(3), report.history(1, 2));
+    }
+
+    #[test]
+    fn RemovalReport_ascii_frame__for_rounds__shows_erosion() {
+        let mut storage = PaperRollStorage::new(String::from("@@@@\n@@@@\n@@@@\n"));
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        assert_eq!("@@@@\n@@@@\n@@@@\n", report.ascii_frame(0));
+        assert_eq!("x@@x\n@@@@\nx@@x\n", report.ascii_frame(1));
+        assert_eq!(".@@.\nx@@x\n.@@.\n", report.ascii_frame(2));
+        assert_eq!(".xx.\n.@@.\n.xx.\n", report.ascii_frame(3));
+        assert_eq!("....\n.xx.\n....\n", report.ascii_frame(4));
+        let frames = report.ascii_frames();
+        assert!(frames.starts_with("== round 0 ==\n@@@@\n"));
+        assert!(frames.ends_with("== round 4 ==\n....\n.xx.\n....\n\n"));
+    }
+
+    #[test]
+    fn RemovalReport_ppm_frame__for_round__colours_taken_rolls() {
+        let mut storage = PaperRollStorage::new(String::from("@@@@\n@@@@\n@@@@\n"));
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        let image = report.ppm_frame(3);
+        let header = format!("P6\n{} {}\n255\n", 4 * PIXELS_PER_CELL, 3 * PIXELS_PER_CELL);
+        assert!(image.starts_with(header.as_bytes()));
+        let pixels = &image[header.len()..];
+        assert_eq!(4 * 3 * PIXELS_PER_CELL * PIXELS_PER_CELL * 3, pixels.len());
+        // the corner was taken in round 1, its neighbour in round 3
+        assert_eq!(&TAKEN_BEFORE_COLOUR, &pixels[0..3]);
+        let second_cell = PIXELS_PER_CELL * 3;
+        assert_eq!(&TAKEN_COLOUR, &pixels[second_cell..second_cell + 3]);
+    }
+
+    #[test]
+    fn PaperRollStorage_count_neighbours_in_row__for_rules__matches_count_neighbours() {
+        let mut seed = 100;
+        for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+            for radius in 1..=3 {
+                for wrap in [false, true] {
+                    for cols in [1, 5, 63, 64, 65, 130] {
+                        seed += 1;
+                        let input = generate_storage(7, cols, 55, seed);
+                        let storage = PaperRollStorage::new(input).with_rule(rule(
+                            neighbourhood,
+                            radius,
+                            4,
+                            wrap,
+                        ));
+                        for row in 0..storage.rows {
+                            let expected: Vec<i32> = (0..storage.cols)
+                                .map(|col| storage.count_neighbours(row, col))
+                                .collect();
+                            assert_eq!(
+                                expected,
+                                storage.count_neighbours_in_row(row),
+                                "{:?} with {} columns, row {}",
+                                neighbourhood,
+                                cols,
+                                row
+                            );
+                        }
+                    }
                 }
             }
         }
+    }
+
+    #[test]
+    fn PaperRollStorage_take_paper_roll__across_words__keeps_other_bits() {
+        let input = format!("{}\n", "@".repeat(130));
+        let mut storage = PaperRollStorage::new(input);
+        storage.take_paper_roll(0, 64);
+        assert!(storage.is_paper_roll(0, 63));
+        assert!(!storage.is_paper_roll(0, 64));
+        assert!(storage.is_paper_roll(0, 65));
+        assert!(storage.is_paper_roll(0, 129));
+        assert!(!storage.is_paper_roll(0, 130));
+        // the taken roll still has both neighbours, its neighbours lost one
+        assert_eq!(
+            vec![1, 2, 1],
+            storage.count_neighbours_in_row(0)[63..66].to_vec()
+        );
+    }
+
+    #[test]
+    fn PaperRollStorage_try_from__with_crlf_and_trailing_blank_line__reads_grid() {
+        let storage = PaperRollStorage::try_from(".@.\r\n@@@\r\n\r\n").unwrap();
+        assert_eq!((2, 3), (storage.rows, storage.cols));
+        assert!(storage.is_paper_roll(0, 1));
+        assert!(!storage.is_paper_roll(0, 2));
+        assert_eq!(3, storage.count_neighbours(0, 1));
+    }
+
+    #[test]
+    fn PaperRollStorage_try_from__with_ragged_rows__reports_widths() {
+        assert_eq!(
+            Err(ParseError {
+                row: 3,
+                kind: ParseErrorKind::RaggedRow {
+                    expected: 3,
+                    actual: 2
+                }
+            }),
+            PaperRollStorage::try_from("...\n@@@\n@@\n").map(|_| ())
+        );
+        // a blank line within the storage is a row without cells
+        assert_eq!(
+            Err(ParseError {
+                row: 2,
+                kind: ParseErrorKind::RaggedRow {
+                    expected: 3,
+                    actual: 0
+                }
+            }),
+            PaperRollStorage::try_from("...\n\n@@@\n").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn PaperRollStorage_try_from__with_invalid_byte__reports_position() {
+        let result = PaperRollStorage::try_from("...\n.@#\n").map(|_| ());
+        assert_eq!(
+            Err(ParseError {
+                row: 2,
+                kind: ParseErrorKind::UnexpectedByte {
+                    column: 3,
+                    byte: b'#'
+                }
+            }),
+            result
+        );
+        assert_eq!(
+            "unexpected byte '#' at row 2, column 3",
+            result.unwrap_err().to_string()
+        );
+        // a stray carriage return is no line end
+        assert_eq!(
+            Err(ParseError {
+                row: 1,
+                kind: ParseErrorKind::UnexpectedByte {
+                    column: 2,
+                    byte: b'\r'
+                }
+            }),
+            PaperRollStorage::try_from(".\r.\n").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_take_all__for_example__matches_dense() {
+        let mut storage = SparsePaperRollStorage::try_from(EXAMPLE).unwrap();
+        assert_eq!(13, storage.count_accessible());
+        assert_eq!(43, storage.take_all_by_worklist());
+    }
 
-        available_paper_rolls += taken_this_time;
-        if taken_this_time == 0 {
-            break;
+    #[test]
+    fn SparsePaperRollStorage_take_all_by_rounds__for_example__matches_dense() {
+        for mode in [RemovalMode::Sequential, RemovalMode::Synchronous] {
+            let dense = PaperRollStorage::new(EXAMPLE.to_string()).take_all_by_rounds(mode);
+            let sparse = SparsePaperRollStorage::try_from(EXAMPLE)
+                .unwrap()
+                .take_all_by_rounds(mode);
+            assert_eq!(dense.taken_per_round, sparse.taken_per_round);
+            assert_eq!(dense.ascii_frames(), sparse.ascii_frames());
         }
     }
-    println!("[Part 2] paper rolls available: {}", available_paper_rolls);
 
-    println!(
-        "evaluation took {} ms",
-        start.elapsed().as_nanos() as f64 / 1e6
-    )
+    #[test]
+    fn SparsePaperRollStorage_take_all_by_rounds__at_negative_coordinates__draws_bounding_box() {
+        let mut storage: SparsePaperRollStorage = [(-5, -5), (-5, -4), (-4, -5), (-3, -3)]
+            .into_iter()
+            .collect();
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        assert_eq!(vec![4], report.taken_per_round);
+        assert_eq!(CellHistory::TakenInRound(1), report.history(-3, -3));
+        assert_eq!(CellHistory::Empty, report.history(-4, -4));
+        assert_eq!("@@.\n@..\n..@\n", report.ascii_frame(0));
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_count_neighbours__at_negative_coordinates__counts_around() {
+        let storage: SparsePaperRollStorage = [
+            (-1_000_000, -5),
+            (-1_000_000, -4),
+            (-999_999, -5),
+            (-1_000_001, -6),
+        ]
+        .into_iter()
+        .collect();
+        assert!(storage.is_paper_roll(-999_999, -5));
+        assert!(!storage.is_paper_roll(0, 0));
+        assert_eq!(3, storage.count_neighbours(-1_000_000, -5));
+        assert_eq!(3, storage.count_neighbours(-1_000_001, -5));
+        assert_eq!(4, storage.count_accessible());
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_parse__with_origin__shifts_positions() {
+        let storage = SparsePaperRollStorage::parse("@.\n.@@\n", (-3, -1)).unwrap();
+        assert_eq!(vec![(-3, -1), (-2, 0), (-2, 1)], storage.paper_rolls());
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_parse__beyond_coordinates__reports_position() {
+        assert_eq!(
+            Err(ParseError {
+                row: 2,
+                kind: ParseErrorKind::OutOfRange { column: 3 }
+            }),
+            SparsePaperRollStorage::parse("...\n.@@\n", (0, i32::MAX - 1)).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_count_neighbours__at_coordinate_limits__does_not_wrap() {
+        let mut storage: SparsePaperRollStorage = [
+            (i32::MAX, i32::MAX),
+            (i32::MAX, i32::MAX - 1),
+            (i32::MIN, i32::MIN),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(1, storage.count_neighbours(i32::MAX, i32::MAX));
+        assert_eq!(0, storage.count_neighbours(i32::MIN, i32::MIN));
+        assert_eq!(5, storage.neighbours(i32::MIN, 0).len());
+        assert_eq!(3, storage.take_all_by_worklist());
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_take_all__far_apart__takes_each_cluster() {
+        // two full 3x3 blocks far apart, each erodes completely
+        let mut storage: SparsePaperRollStorage = (0..3)
+            .flat_map(|r| (0..3).map(move |c| (r, c)))
+            .flat_map(|(r, c)| [(r - 2_000_000, c + 5), (r + 2_000_000, c - 7)])
+            .collect();
+        assert_eq!(18, storage.paper_rolls().len());
+        assert_eq!(8, storage.count_accessible());
+        assert_eq!(18, storage.take_all_by_worklist());
+        assert!(storage.paper_rolls().is_empty());
+    }
+
+    #[test]
+    fn export__far_apart__refuses_without_writing() {
+        let mut storage: SparsePaperRollStorage = (0..3)
+            .flat_map(|r| (0..3).map(move |c| (r, c)))
+            .flat_map(|(r, c)| [(r - 2_000_000, c + 5), (r + 2_000_000, c - 7)])
+            .collect();
+        let report = storage.take_all_by_rounds(RemovalMode::Synchronous);
+        assert_eq!(4_000_003 * 15, report.cells());
+
+        let directory = std::env::temp_dir().join("aoc04_export_far_apart");
+        assert_eq!(
+            Err(ExportError {
+                cells: 4_000_003 * 15
+            }),
+            export(&report, directory.to_str().unwrap())
+        );
+        assert!(!directory.exists());
+    }
+
+    #[test]
+    fn SparsePaperRollStorage_take_all__for_generated_storages__matches_dense() {
+        let mut seed = 200;
+        for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+            for radius in 1..=2 {
+                seed += 1;
+                let rule = rule(neighbourhood, radius, 2 + radius * 2, false);
+                let input = generate_storage(20, 70, 55, seed);
+                let mut dense = PaperRollStorage::new(input.clone()).with_rule(rule);
+                let mut sparse = SparsePaperRollStorage::try_from(input.as_str())
+                    .unwrap()
+                    .with_rule(rule);
+
+                assert_eq!(dense.count_accessible(), sparse.count_accessible());
+                assert_eq!(dense.take_all_by_worklist(), sparse.take_all_by_worklist());
+                assert_eq!(dense.paper_rolls(), sparse.paper_rolls(), "{:?}", rule);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn SparsePaperRollStorage_with_rule__wrapping__panics() {
+        SparsePaperRollStorage::try_from("@@\n")
+            .unwrap()
+            .with_rule(rule(Neighbourhood::Moore, 1, 4, true));
+    }
 }